@@ -1,75 +1,220 @@
-use reqwest::Request;
-use tokio::sync::{mpsc, Mutex};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    task::{Context, Poll},
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{mpsc, oneshot, Mutex};
 
 use crate::{
+    client::Client,
     error::Error,
-    response::{APIResponse, ActionData},
+    history::{Entry, History},
+    metrics::Metrics,
+    request::Action,
+    response::ActionData,
+    state::State,
 };
 
+struct QueuedAction {
+    task: String,
+    description: String,
+    action: Action,
+    result: oneshot::Sender<Result<ActionData, Error>>,
+}
+
+/// Resolves to the result of a queued action once it has been executed.
+///
+/// Dropping the handle does not cancel the action, it is still executed and
+/// the result is discarded.
+pub struct ActionHandle(oneshot::Receiver<Result<ActionData, Error>>);
+
+impl Future for ActionHandle {
+    type Output = Result<ActionData, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0)
+            .poll(cx)
+            .map(|result| result.unwrap_or(Err(Error::ActionDropped)))
+    }
+}
+
 pub struct ActionQueue {
-    client: reqwest::Client,
-    sender: mpsc::Sender<(String, Request)>,
-    receiver: Mutex<mpsc::Receiver<(String, Request)>>,
+    client: Client,
+    sender: mpsc::Sender<QueuedAction>,
+    receiver: Mutex<mpsc::Receiver<QueuedAction>>,
+    // action rejected because of a cooldown, executed again before the next queued action
+    retry: Mutex<Option<QueuedAction>>,
     character_name: String,
+    state: State,
+    metrics: Option<Metrics>,
+    history: Option<History>,
+    // when the cooldown of the last executed action expired, to measure idle time
+    last_expiration: Mutex<Option<DateTime<Utc>>>,
+    executed: AtomicU64,
+    // actions pushed, or rejected because of a cooldown, that haven't started executing
+    waiting: AtomicUsize,
 }
 
 impl ActionQueue {
-    pub fn new(client: reqwest::Client, character_name: String) -> Self {
+    pub fn new(client: Client, character_name: String, state: State) -> Self {
         let (tx, rx) = mpsc::channel(1);
 
         Self {
             client,
             sender: tx,
             receiver: Mutex::new(rx),
+            retry: Mutex::new(None),
             character_name,
+            state,
+            metrics: None,
+            history: None,
+            last_expiration: Mutex::new(None),
+            executed: AtomicU64::new(0),
+            waiting: AtomicUsize::new(0),
         }
     }
 
-    pub async fn push(&self, description: impl Into<String>, task: Request) {
+    /// Record executed actions in `metrics`
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+    }
+
+    /// Log executed actions to `history`
+    pub fn set_history(&mut self, history: History) {
+        self.history = Some(history);
+    }
+
+    /// Number of actions that succeeded so far
+    pub fn executed(&self) -> u64 {
+        self.executed.load(Ordering::Relaxed)
+    }
+
+    /// Queue an action of `task`, a description of the task it is for
+    pub async fn push(&self, task: impl Into<String>, action: Action) -> ActionHandle {
+        let (tx, rx) = oneshot::channel();
+        let task = task.into();
+
+        self.set_waiting(self.waiting.fetch_add(1, Ordering::Relaxed) + 1);
         self.sender
-            .send((description.into(), task))
+            .send(QueuedAction {
+                description: format!("{task} {action:?}"),
+                task,
+                action,
+                result: tx,
+            })
             .await
             .expect("mpsc send failed");
+
+        ActionHandle(rx)
     }
 
-    /// Pop and execute the next action in the queue, returning the remaining cooldown seconds
-    pub async fn pop_execute(&self) -> Result<Option<i32>, Error> {
-        let (description, request) = {
-            let mut rx = self.receiver.lock().await;
+    /// Pop and execute the next action in the queue, returning when its cooldown expires.
+    ///
+    /// The action result is delivered to its `ActionHandle` and the character state is
+    /// updated from the response. Actions rejected because the
+    /// character is in cooldown return `Error::Cooldown` and are retried on the next call.
+    pub async fn pop_execute(&self) -> Result<Option<DateTime<Utc>>, Error> {
+        let queued = match self.retry.lock().await.take() {
+            Some(queued) => queued,
+            None => {
+                let mut rx = self.receiver.lock().await;
 
-            match rx.recv().await {
-                Some(req) => req,
-                None => {
-                    return Ok(None);
+                match rx.recv().await {
+                    Some(queued) => queued,
+                    None => {
+                        return Ok(None);
+                    }
                 }
             }
         };
 
-        tracing::info!("{} -> {}", self.character_name, description);
+        self.set_waiting(self.waiting.fetch_sub(1, Ordering::Relaxed) - 1);
+        tracing::info!("{} -> {}", self.character_name, queued.description);
 
-        let res = self
+        if let Some(metrics) = &self.metrics {
+            self.record_wait(metrics).await;
+        }
+
+        let result = self
             .client
-            .execute(request)
-            .await?
-            .json::<APIResponse<ActionData>>()
-            .await?;
-
-        match res {
-            APIResponse {
-                data: Some(data),
-                error: None,
-            } => Ok(Some(data.cooldown.remaining_seconds as i32)),
-            APIResponse {
-                data: None,
-                error: Some(error),
-            } => {
-                match error.code {
-                    490 => Ok(None), // character already at location
-                    499 => Err(Error::Cooldown),
-                    _ => Err(Error::APIError(error)),
+            .action(&self.character_name, &queued.action)
+            .await;
+
+        if let Some(history) = &self.history {
+            let entry = self.state.read(|before| {
+                Entry::new(
+                    self.client.clock().now(),
+                    &self.character_name,
+                    &queued.task,
+                    &queued.action,
+                    before,
+                    &result,
+                )
+            });
+
+            if let Err(e) = history.record(&entry) {
+                tracing::warn!(error = %e, "failed to record action history");
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            match &result {
+                Ok(data) => self.state.read(|before| {
+                    metrics.action(&self.character_name, &queued.action, before, data)
+                }),
+                Err(e) => {
+                    if let Some(code) = e.code() {
+                        metrics.error(&self.character_name, &queued.action, code);
+                    }
                 }
             }
-            _ => Err(Error::InvalidAPIResponse),
+        }
+
+        let cooldown = match &result {
+            Ok(data) => {
+                self.state.set(data.character.clone());
+                self.executed.fetch_add(1, Ordering::Relaxed);
+                *self.last_expiration.lock().await = Some(data.cooldown.expiration);
+                Some(data.cooldown.expiration)
+            }
+            Err(Error::Cooldown) => {
+                self.set_waiting(self.waiting.fetch_add(1, Ordering::Relaxed) + 1);
+                *self.retry.lock().await = Some(queued);
+                return Err(Error::Cooldown);
+            }
+            Err(Error::AlreadyAtDestination) => None,
+            Err(e) => {
+                tracing::warn!(error = %e, "{} -> {} failed", self.character_name, queued.description);
+                None
+            }
+        };
+
+        // the handle may have been dropped by a caller that doesn't need the result
+        let _ = queued.result.send(result);
+
+        Ok(cooldown)
+    }
+
+    // Record how long the character sat off cooldown waiting for this action. Until the action
+    // succeeds and sets its own cooldown, the character is busy from now on.
+    async fn record_wait(&self, metrics: &Metrics) {
+        let now = self.client.clock().now();
+        let mut last_expiration = self.last_expiration.lock().await;
+
+        if let Some(expiration) = *last_expiration {
+            let idle = (now - expiration).to_std().unwrap_or_default();
+            metrics.idle(&self.character_name, idle);
+        }
+
+        *last_expiration = Some(now);
+    }
+
+    fn set_waiting(&self, waiting: usize) {
+        if let Some(metrics) = &self.metrics {
+            metrics.queue_depth(&self.character_name, waiting);
         }
     }
 }
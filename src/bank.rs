@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use crate::{client::Client, error::Error, response::BankDetails};
+
+/// The account's bank, shared by all of its characters. Nothing is cached since any of the
+/// characters can change it at any time.
+pub struct Bank<'a> {
+    client: &'a Client,
+}
+
+impl<'a> Bank<'a> {
+    pub fn new(client: &'a Client) -> Self {
+        Self { client }
+    }
+
+    /// Quantity of every item in the bank by code, fetching every page
+    pub async fn items(&self) -> Result<HashMap<String, i64>, Error> {
+        let mut items = HashMap::new();
+
+        for item in self.client.all_bank_items().await? {
+            *items.entry(item.code).or_default() += item.quantity;
+        }
+
+        Ok(items)
+    }
+
+    /// Quantity of an item in the bank
+    pub async fn count(&self, code: &str) -> Result<i64, Error> {
+        Ok(self.items().await?.get(code).copied().unwrap_or_default())
+    }
+
+    /// Slots, expansions and gold
+    pub async fn details(&self) -> Result<BankDetails, Error> {
+        self.client.bank_details().await
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::response::Character;
+
+/// What changed between two snapshots of a character, e.g. before and after an action:
+/// xp gained per skill, gold and inventory items. Only non-zero changes are kept.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Changes {
+    pub xp: BTreeMap<String, i64>,
+    pub gold: i64,
+    pub items: BTreeMap<String, i64>,
+}
+
+impl Changes {
+    pub fn between(before: &Character, after: &Character) -> Self {
+        let xp = skills(before)
+            .into_iter()
+            .zip(skills(after))
+            .map(|((skill, before), (_, after))| (skill.to_string(), xp_gained(before, after)))
+            .filter(|(_, xp)| *xp != 0)
+            .collect();
+
+        let mut items = BTreeMap::new();
+        for slot in before.inventory.items() {
+            *items.entry(slot.code.clone()).or_default() -= slot.quantity;
+        }
+        for slot in after.inventory.items() {
+            *items.entry(slot.code.clone()).or_default() += slot.quantity;
+        }
+        items.retain(|_, quantity| *quantity != 0);
+
+        Self {
+            xp,
+            gold: after.gold - before.gold,
+            items,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xp.is_empty() && self.gold == 0 && self.items.is_empty()
+    }
+}
+
+// (level, xp, max xp) of every skill, `combat` being the character level
+fn skills(character: &Character) -> [(&'static str, (i64, i64, i64)); 8] {
+    let c = character;
+
+    [
+        ("combat", (c.level, c.xp, c.max_xp)),
+        ("mining", (c.mining_level, c.mining_xp, c.mining_max_xp)),
+        (
+            "woodcutting",
+            (c.woodcutting_level, c.woodcutting_xp, c.woodcutting_max_xp),
+        ),
+        ("fishing", (c.fishing_level, c.fishing_xp, c.fishing_max_xp)),
+        (
+            "weaponcrafting",
+            (
+                c.weaponcrafting_level,
+                c.weaponcrafting_xp,
+                c.weaponcrafting_max_xp,
+            ),
+        ),
+        (
+            "gearcrafting",
+            (
+                c.gearcrafting_level,
+                c.gearcrafting_xp,
+                c.gearcrafting_max_xp,
+            ),
+        ),
+        (
+            "jewelrycrafting",
+            (
+                c.jewelrycrafting_level,
+                c.jewelrycrafting_xp,
+                c.jewelrycrafting_max_xp,
+            ),
+        ),
+        ("cooking", (c.cooking_level, c.cooking_xp, c.cooking_max_xp)),
+    ]
+}
+
+// xp resets on level up, so count what was left of the old level. Levels skipped entirely
+// aren't counted since their max xp isn't known.
+fn xp_gained(
+    (before_level, before_xp, before_max_xp): (i64, i64, i64),
+    (after_level, after_xp, _): (i64, i64, i64),
+) -> i64 {
+    if after_level > before_level {
+        (before_max_xp - before_xp) + after_xp
+    } else {
+        after_xp - before_xp
+    }
+}
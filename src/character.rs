@@ -1,42 +1,181 @@
-use std::time::Duration;
-
-use reqwest::{Method, Request};
-use serde_json::json;
+use std::{sync::Arc, time::Duration};
 
 use crate::{
-    action_queue::ActionQueue,
-    config::TaskName,
+    action_queue::{ActionHandle, ActionQueue},
+    bank::Bank,
+    client::Client,
+    config::{DepositPolicy, HealPolicy, Position, Task},
     error::Error,
-    response::{self, APIResponse, InventorySlot},
+    game_data::GameData,
+    ge::GrandExchange,
+    history::History,
+    inventory::Inventory,
+    metrics::Metrics,
+    request::{
+        Action, CraftingRequest, EquipRequest, GETransactionRequest, GoldRequest, MoveRequest,
+        SimpleItemRequest, UnequipRequest,
+    },
+    response,
+    state::State,
 };
+// Extra delay before retrying an action rejected because of a cooldown
+const COOLDOWN_RETRY_DELAY: Duration = Duration::from_millis(500);
+
 pub struct Character {
-    client: reqwest::Client,
+    client: Client,
     name: String,
     pub(super) queue: ActionQueue,
-    token: String,
+    state: State,
+    game: Arc<GameData>,
+    heal_policy: HealPolicy,
+    deposit_policy: DepositPolicy,
 }
 
 impl Character {
-    pub fn new(client: reqwest::Client, name: String, token: String) -> Self {
+    pub fn new(
+        client: Client,
+        name: String,
+        initial_state: response::Character,
+        game: Arc<GameData>,
+    ) -> Self {
+        let state = State::new(initial_state);
+
         Self {
-            queue: ActionQueue::new(client.clone(), name.clone()),
+            queue: ActionQueue::new(client.clone(), name.clone(), state.clone()),
             client,
             name,
-            token,
+            state,
+            game,
+            heal_policy: HealPolicy::default(),
+            deposit_policy: DepositPolicy::default(),
         }
     }
 
+    /// When the character heals between fights
+    pub fn with_heal_policy(mut self, heal_policy: HealPolicy) -> Self {
+        self.heal_policy = heal_policy;
+        self
+    }
+
+    /// Record the character's actions in `metrics`
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.queue.set_metrics(metrics);
+        self
+    }
+
+    /// Log the character's actions to `history`
+    pub fn with_history(mut self, history: History) -> Self {
+        self.queue.set_history(history);
+        self
+    }
+
+    /// Fetch the character from the API and create it with its current state
+    pub async fn load(client: Client, name: String, game: Arc<GameData>) -> Result<Self, Error> {
+        let initial_state = client.character(&name).await?;
+
+        Ok(Self::new(client, name, initial_state, game))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    pub fn game(&self) -> &GameData {
+        &self.game
+    }
+
+    /// What the character deposits in the bank
+    pub fn with_deposit_policy(mut self, deposit_policy: DepositPolicy) -> Self {
+        self.deposit_policy = deposit_policy;
+        self
+    }
+
+    /// The bank of the character's account
+    pub fn bank(&self) -> Bank<'_> {
+        Bank::new(&self.client)
+    }
+
+    pub fn grand_exchange(&self) -> GrandExchange<'_> {
+        GrandExchange::new(&self.client)
+    }
+
+    pub fn heal_policy(&self) -> &HealPolicy {
+        &self.heal_policy
+    }
+
+    pub fn deposit_policy(&self) -> &DepositPolicy {
+        &self.deposit_policy
+    }
+
+    /// Position of the map tile with content `code` nearest to the character
+    pub async fn locate(&self, code: &str) -> Result<Position, Error> {
+        let from = self.read_state(|character| (character.x, character.y));
+
+        match self.game.map.nearest(code, from).await? {
+            Some(tile) => Ok(Position {
+                x: tile.x as i32,
+                y: tile.y as i32,
+            }),
+            None => Err(Error::ContentNotFound(code.into())),
+        }
+    }
+
+    /// A snapshot of the latest known character state, as of the last executed action
+    pub fn state(&self) -> response::Character {
+        self.state.get()
+    }
+
+    /// Read from the latest known character state without cloning it
+    pub fn read_state<R>(&self, f: impl FnOnce(&response::Character) -> R) -> R {
+        self.state.read(f)
+    }
+
+    /// Number of the character's actions that succeeded so far
+    pub fn executed_actions(&self) -> u64 {
+        self.queue.executed()
+    }
+
+    /// Fetch the character from the API, replacing the cached state
+    pub async fn refresh_state(&self) -> Result<response::Character, Error> {
+        let character = self.client.character(&self.name).await?;
+        self.state.set(character.clone());
+
+        Ok(character)
+    }
+
     pub async fn execution_loop(&self) -> Result<(), Error> {
+        let clock = self.client.clock();
+
+        // wait out any cooldown from before we started
+        if let Some(expiration) = self.read_state(|character| character.cooldown_expiration) {
+            clock.sleep_until(expiration).await;
+        }
+
         loop {
-            let cooldown_remaining_seconds = match self.queue.pop_execute().await {
-                Ok(Some(seconds)) => seconds,
+            let cooldown_expiration = match self.queue.pop_execute().await {
+                Ok(Some(expiration)) => expiration,
                 Ok(None) => {
                     continue;
                 }
                 Err(Error::Cooldown) => {
                     let name = &self.name;
-                    tracing::warn!(character = %name, "on cooldown");
-                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    tracing::warn!(character = %name, skew = %clock.skew(), "on cooldown");
+
+                    // the action is retried, fetch the character to find out when
+                    let expiration = match self.refresh_state().await {
+                        Ok(character) => character.cooldown_expiration,
+                        Err(e) => {
+                            tracing::error!(error = ?e, "error refreshing character");
+                            None
+                        }
+                    };
+
+                    clock.sleep_until(expiration.unwrap_or_default()).await;
+                    tokio::time::sleep(COOLDOWN_RETRY_DELAY).await;
                     continue;
                 }
                 Err(e) => {
@@ -45,271 +184,243 @@ impl Character {
                 }
             };
 
-            tokio::time::sleep(Duration::from_secs(cooldown_remaining_seconds as u64)).await;
+            clock.sleep_until(cooldown_expiration).await;
         }
     }
 
-    fn build_request(
+    async fn action(&self, current_task: &Task, action: Action) -> Result<ActionHandle, Error> {
+        Ok(self.queue.push(format!("{current_task:?}"), action).await)
+    }
+
+    pub async fn move_to(
         &self,
-        current_task: TaskName,
-        method: Method,
-        path: &str,
-        body: Option<serde_json::Value>,
-    ) -> Result<(String, Request), Error> {
-        let mut req = self
-            .client
-            .request(
-                method.clone(),
-                format!("https://api.artifactsmmo.com/{}", path),
-            )
-            .header("Authorization", &self.token);
-
-        let mut description = format!("{current_task:?} {method} {path}");
-
-        if let Some(body) = body {
-            req = req.json(&body);
-            description = format!("{description} {}", serde_json::to_string(&body)?);
-        }
+        current_task: &Task,
+        x: i32,
+        y: i32,
+    ) -> Result<ActionHandle, Error> {
+        self.action(
+            current_task,
+            Action::Move(MoveRequest {
+                x: x.into(),
+                y: y.into(),
+            }),
+        )
+        .await
+    }
 
-        Ok((description, req.build()?))
+    pub async fn fight(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::Fight).await
     }
 
-    async fn action(
+    pub async fn gathering(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::Gathering).await
+    }
+
+    /// Rest until hp is full
+    pub async fn rest(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::Rest).await
+    }
+
+    /// Use a consumable from the inventory, e.g. eat food to heal
+    pub async fn use_item(
         &self,
-        current_task: TaskName,
-        method: Method,
-        action: &str,
-        body: Option<serde_json::Value>,
-    ) -> Result<(), Error> {
-        let (description, req) = self.build_request(
+        current_task: &Task,
+        code: &str,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
+        self.action(
             current_task,
-            method,
-            &format!("my/{}/action/{}", self.name, action),
-            body,
-        )?;
+            Action::Use(SimpleItemRequest {
+                code: code.into(),
+                quantity,
+            }),
+        )
+        .await
+    }
 
-        self.queue.push(description, req).await;
+    pub async fn unequip(&self, current_task: &Task, slot: &str) -> Result<ActionHandle, Error> {
+        self.action(
+            current_task,
+            Action::Unequip(UnequipRequest { slot: slot.into() }),
+        )
+        .await
+    }
 
-        Ok(())
+    pub async fn crafting(
+        &self,
+        current_task: &Task,
+        code: &str,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
+        self.action(
+            current_task,
+            Action::Crafting(CraftingRequest {
+                code: code.into(),
+                quantity,
+            }),
+        )
+        .await
     }
 
-    pub async fn move_to(&self, current_task: TaskName, x: i32, y: i32) -> Result<(), Error> {
+    pub async fn equip(
+        &self,
+        current_task: &Task,
+        code: &str,
+        slot: &str,
+    ) -> Result<ActionHandle, Error> {
         self.action(
             current_task,
-            Method::POST,
-            "move",
-            Some(json!({
-                "x": x,
-                "y": y
-            })),
+            Action::Equip(EquipRequest {
+                code: code.into(),
+                slot: slot.into(),
+            }),
         )
         .await
     }
 
-    pub async fn fight(&self, current_task: TaskName) -> Result<(), Error> {
-        self.action(current_task, Method::POST, "fight", None).await
+    pub async fn deposit(
+        &self,
+        current_task: &Task,
+        code: &str,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
+        self.action(
+            current_task,
+            Action::BankDeposit(SimpleItemRequest {
+                code: code.into(),
+                quantity,
+            }),
+        )
+        .await
     }
 
-    pub async fn gathering(&self, current_task: TaskName) -> Result<(), Error> {
-        self.action(current_task, Method::POST, "gathering", None)
-            .await
+    pub async fn withdraw(
+        &self,
+        current_task: &Task,
+        code: &str,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
+        self.action(
+            current_task,
+            Action::BankWithdraw(SimpleItemRequest {
+                code: code.into(),
+                quantity,
+            }),
+        )
+        .await
     }
 
-    pub async fn unequip(&self, current_task: TaskName, slot: &str) -> Result<(), Error> {
+    pub async fn deposit_gold(
+        &self,
+        current_task: &Task,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
         self.action(
             current_task,
-            Method::POST,
-            "unequip",
-            Some(json!({
-                "slot": slot
-            })),
+            Action::BankDepositGold(GoldRequest { quantity }),
         )
         .await
     }
 
-    pub async fn crafting(&self, current_task: TaskName, code: &str) -> Result<(), Error> {
+    pub async fn withdraw_gold(
+        &self,
+        current_task: &Task,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
         self.action(
             current_task,
-            Method::POST,
-            "crafting",
-            Some(json!({
-                "code": code,
-            })),
+            Action::BankWithdrawGold(GoldRequest { quantity }),
         )
         .await
     }
 
-    pub async fn equip(&self, current_task: TaskName, code: &str, slot: &str) -> Result<(), Error> {
+    /// Buy more bank slots with the character's gold
+    pub async fn buy_bank_expansion(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::BankBuyExpansion).await
+    }
+
+    /// Buy `quantity` of an item at `price` each, which must be the current buy price
+    pub async fn ge_buy(
+        &self,
+        current_task: &Task,
+        code: &str,
+        quantity: i64,
+        price: i64,
+    ) -> Result<ActionHandle, Error> {
         self.action(
             current_task,
-            Method::POST,
-            "equip",
-            Some(json!({
-                "code": code,
-                "slot": slot,
-            })),
+            Action::GEBuy(GETransactionRequest {
+                code: code.into(),
+                quantity,
+                price,
+            }),
         )
         .await
     }
 
-    pub async fn deposit(
+    /// Sell `quantity` of an item at `price` each, which must be the current sell price
+    pub async fn ge_sell(
         &self,
-        current_task: TaskName,
+        current_task: &Task,
         code: &str,
         quantity: i64,
-    ) -> Result<(), Error> {
+        price: i64,
+    ) -> Result<ActionHandle, Error> {
         self.action(
             current_task,
-            Method::POST,
-            "bank/deposit",
-            Some(json!({
-                "code": code,
-                "quantity": quantity,
-            })),
+            Action::GESell(GETransactionRequest {
+                code: code.into(),
+                quantity,
+                price,
+            }),
         )
         .await
     }
 
-    pub async fn inventory(
+    /// Accept a new task, the character must be on a tasks master's tile
+    pub async fn task_new(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::TaskNew).await
+    }
+
+    /// Turn in the completed task for a tasks coin
+    pub async fn task_complete(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::TaskComplete).await
+    }
+
+    /// Abandon the current task, which costs a tasks coin
+    pub async fn task_cancel(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::TaskCancel).await
+    }
+
+    /// Hand items over to the items task, the character must be on an items tasks master's tile
+    pub async fn task_trade(
         &self,
-        current_task: TaskName,
-    ) -> Result<(i64, Vec<InventorySlot>), Error> {
-        let (_, req) = self.build_request(
+        current_task: &Task,
+        code: &str,
+        quantity: i64,
+    ) -> Result<ActionHandle, Error> {
+        self.action(
             current_task,
-            Method::GET,
-            &format!("characters/{}", self.name),
-            None,
-        )?;
-        let response: APIResponse<response::Character> =
-            self.client.execute(req).await?.json().await?;
-
-        let data = match response.data {
-            Some(data) => data,
-            None => return Err(Error::InvalidAPIResponse),
-        };
-
-        let mut slots: Vec<InventorySlot> = vec![];
-
-        slots.push(InventorySlot {
-            name: "inventory_slot1".into(),
-            code: data.inventory_slot1,
-            quantity: data.inventory_slot1_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot2".into(),
-            code: data.inventory_slot2,
-            quantity: data.inventory_slot2_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot3".into(),
-            code: data.inventory_slot3,
-            quantity: data.inventory_slot3_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot4".into(),
-            code: data.inventory_slot4,
-            quantity: data.inventory_slot4_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot5".into(),
-            code: data.inventory_slot5,
-            quantity: data.inventory_slot5_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot6".into(),
-            code: data.inventory_slot6,
-            quantity: data.inventory_slot6_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot7".into(),
-            code: data.inventory_slot7,
-            quantity: data.inventory_slot7_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot8".into(),
-            code: data.inventory_slot8,
-            quantity: data.inventory_slot8_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot9".into(),
-            code: data.inventory_slot9,
-            quantity: data.inventory_slot9_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot10".into(),
-            code: data.inventory_slot10,
-            quantity: data.inventory_slot10_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot11".into(),
-            code: data.inventory_slot11,
-            quantity: data.inventory_slot11_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot12".into(),
-            code: data.inventory_slot12,
-            quantity: data.inventory_slot12_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot13".into(),
-            code: data.inventory_slot13,
-            quantity: data.inventory_slot13_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot14".into(),
-            code: data.inventory_slot14,
-            quantity: data.inventory_slot14_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot15".into(),
-            code: data.inventory_slot15,
-            quantity: data.inventory_slot15_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot16".into(),
-            code: data.inventory_slot16,
-            quantity: data.inventory_slot16_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot17".into(),
-            code: data.inventory_slot17,
-            quantity: data.inventory_slot17_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot18".into(),
-            code: data.inventory_slot18,
-            quantity: data.inventory_slot18_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot19".into(),
-            code: data.inventory_slot19,
-            quantity: data.inventory_slot19_quantity,
-        });
-
-        slots.push(InventorySlot {
-            name: "inventory_slot20".into(),
-            code: data.inventory_slot20,
-            quantity: data.inventory_slot20_quantity,
-        });
-
-        Ok((data.inventory_max_items, slots))
+            Action::TaskTrade(SimpleItemRequest {
+                code: code.into(),
+                quantity,
+            }),
+        )
+        .await
+    }
+
+    /// Exchange tasks coins for a random reward
+    pub async fn task_exchange(&self, current_task: &Task) -> Result<ActionHandle, Error> {
+        self.action(current_task, Action::TaskExchange).await
+    }
+
+    /// Quantity of an item in the inventory, from the cached character state
+    pub fn inventory_count(&self, code: &str) -> i64 {
+        self.read_state(|character| character.inventory.count(code))
+    }
+
+    /// Inventory from the cached character state
+    pub fn inventory(&self) -> Inventory {
+        self.read_state(|character| character.inventory.clone())
     }
 }
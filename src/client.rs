@@ -0,0 +1,552 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{AUTHORIZATION, DATE},
+    Method, RequestBuilder,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::time::Instant;
+
+use crate::{
+    clock::Clock,
+    error::Error,
+    mock::World,
+    request::{
+        Action, AddCharacterRequest, CraftingRequest, DeleteCharacterRequest, EquipRequest,
+        GETransactionRequest, GoldRequest, MoveRequest, Page, SimpleItemRequest, UnequipRequest,
+    },
+    response::{
+        APIResponse, Achievement, ActionData, BankDetails, Character, Event, GEItem, Item,
+        LeaderboardEntry, Map, Monster, Resource, SimpleItem, SingleItem, Status, TaskDefinition,
+        TaskRewardDefinition,
+    },
+};
+
+pub const DEFAULT_API_URL: &str = "https://api.artifactsmmo.com";
+
+// Largest page size allowed by the API
+const PAGE_SIZE: i64 = 100;
+
+// Round trip time of requests answered in-process, so that time passes on every request
+// like it does over the network
+const LOCAL_LATENCY: Duration = Duration::from_millis(50);
+
+/// A page of results from a paginated endpoint, `data` holds the items and
+/// `pages` the total number of pages available
+pub type Paginated<T> = APIResponse<Vec<T>>;
+
+/// Where requests are sent
+#[derive(Clone)]
+enum Transport {
+    Http,
+    /// Answered in-process by a simulated world
+    Local(Arc<Mutex<World>>),
+}
+
+/// Typed client for the ArtifactsMMO API
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: String,
+    clock: Clock,
+    transport: Transport,
+}
+
+impl Client {
+    pub fn new(http: reqwest::Client, base_url: impl Into<String>, token: String) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+
+        Self {
+            http,
+            base_url,
+            token,
+            clock: Clock::new(),
+            transport: Transport::Http,
+        }
+    }
+
+    /// A client for a simulated world, with the world's server time given by `clock`
+    pub fn local(world: Arc<Mutex<World>>, clock: Clock) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: "http://localhost".into(),
+            token: String::new(),
+            clock,
+            transport: Transport::Local(world),
+        }
+    }
+
+    /// Server clock estimated from the responses received by this client
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let req = self
+            .http
+            .request(method, format!("{}/{path}", self.base_url));
+
+        // public endpoints don't need a token
+        if self.token.is_empty() {
+            return req;
+        }
+
+        req.header("Authorization", &self.token)
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        req: RequestBuilder,
+    ) -> Result<APIResponse<T>, Error> {
+        let res = match &self.transport {
+            Transport::Http => self.send_http(req).await?,
+            Transport::Local(world) => self.send_local(world, req).await?,
+        };
+
+        match res.error {
+            Some(error) => Err(error.into()),
+            None => Ok(res),
+        }
+    }
+
+    async fn send_http<T: DeserializeOwned>(
+        &self,
+        req: RequestBuilder,
+    ) -> Result<APIResponse<T>, Error> {
+        let sent = Instant::now();
+        let res = req.send().await?;
+        let received = Instant::now();
+
+        if let Some(server_time) = res
+            .headers()
+            .get(DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        {
+            self.clock.observe(
+                sent,
+                received,
+                server_time.with_timezone(&Utc),
+                chrono::Duration::seconds(1),
+            );
+        }
+
+        Ok(res.json::<APIResponse<T>>().await?)
+    }
+
+    async fn send_local<T: DeserializeOwned>(
+        &self,
+        world: &Mutex<World>,
+        req: RequestBuilder,
+    ) -> Result<APIResponse<T>, Error> {
+        let req = req.build()?;
+        let token = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|token| token.to_str().ok())
+            .map(|token| token.trim_start_matches("Bearer "));
+        let body = req
+            .body()
+            .and_then(|body| body.as_bytes())
+            .unwrap_or_default();
+
+        tokio::time::sleep(LOCAL_LATENCY).await;
+
+        let (_, res) = world.lock().expect("world lock poisoned").handle(
+            req.method().as_str(),
+            req.url().path(),
+            req.url().query().unwrap_or_default(),
+            token,
+            body,
+            self.clock.now(),
+        );
+
+        Ok(serde_json::from_value(res)?)
+    }
+
+    async fn data<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<T, Error> {
+        self.send(req).await?.data.ok_or(Error::InvalidAPIResponse)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        self.data(self.request(Method::GET, path)).await
+    }
+
+    async fn get_page<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        page: &Page,
+    ) -> Result<Paginated<T>, Error> {
+        self.send(self.request(Method::GET, path).query(page)).await
+    }
+
+    /// Fetch every page of a paginated endpoint
+    async fn get_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, Error> {
+        let mut all = vec![];
+        let mut page = Page {
+            page: Some(1),
+            size: Some(PAGE_SIZE),
+        };
+
+        loop {
+            let res = self.get_page::<T>(path, &page).await?;
+            let pages = res.pages.unwrap_or_default();
+
+            all.extend(res.data.ok_or(Error::InvalidAPIResponse)?);
+
+            let current = page.page.unwrap_or(1);
+            if current >= pages {
+                return Ok(all);
+            }
+
+            page.page = Some(current + 1);
+        }
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<T, Error> {
+        let mut req = self.request(Method::POST, path);
+
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+
+        self.data(req).await
+    }
+
+    async fn character_action(
+        &self,
+        name: &str,
+        action: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<ActionData, Error> {
+        self.post(&format!("my/{name}/action/{action}"), body).await
+    }
+
+    /// Execute any character action, dispatching to the typed endpoint method
+    pub async fn action(&self, name: &str, action: &Action) -> Result<ActionData, Error> {
+        match action {
+            Action::Move(body) => self.move_character(name, body).await,
+            Action::Fight => self.fight(name).await,
+            Action::Gathering => self.gathering(name).await,
+            Action::Rest => self.rest(name).await,
+            Action::Use(body) => self.use_item(name, body).await,
+            Action::Crafting(body) => self.crafting(name, body).await,
+            Action::Equip(body) => self.equip(name, body).await,
+            Action::Unequip(body) => self.unequip(name, body).await,
+            Action::BankDeposit(body) => self.bank_deposit(name, body).await,
+            Action::BankDepositGold(body) => self.bank_deposit_gold(name, body).await,
+            Action::BankWithdraw(body) => self.bank_withdraw(name, body).await,
+            Action::BankWithdrawGold(body) => self.bank_withdraw_gold(name, body).await,
+            Action::BankBuyExpansion => self.bank_buy_expansion(name).await,
+            Action::Recycling(body) => self.recycling(name, body).await,
+            Action::GEBuy(body) => self.ge_buy(name, body).await,
+            Action::GESell(body) => self.ge_sell(name, body).await,
+            Action::TaskNew => self.task_new(name).await,
+            Action::TaskComplete => self.task_complete(name).await,
+            Action::TaskExchange => self.task_exchange(name).await,
+            Action::TaskCancel => self.task_cancel(name).await,
+            Action::TaskTrade(body) => self.task_trade(name, body).await,
+        }
+    }
+
+    // Server
+
+    pub async fn status(&self) -> Result<Status, Error> {
+        self.get("").await
+    }
+
+    // Characters
+
+    pub async fn create_character(&self, body: &AddCharacterRequest) -> Result<Character, Error> {
+        self.post("characters/create", Some(body)).await
+    }
+
+    pub async fn delete_character(
+        &self,
+        body: &DeleteCharacterRequest,
+    ) -> Result<Character, Error> {
+        self.post("characters/delete", Some(body)).await
+    }
+
+    pub async fn characters(&self, page: &Page) -> Result<Paginated<Character>, Error> {
+        self.get_page("characters/", page).await
+    }
+
+    pub async fn character(&self, name: &str) -> Result<Character, Error> {
+        self.get(&format!("characters/{name}")).await
+    }
+
+    pub async fn my_characters(&self) -> Result<Vec<Character>, Error> {
+        self.get("my/characters").await
+    }
+
+    // Character actions
+
+    pub async fn move_character(
+        &self,
+        name: &str,
+        body: &MoveRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "move", Some(body)).await
+    }
+
+    pub async fn fight(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "fight", None::<&()>).await
+    }
+
+    pub async fn gathering(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "gathering", None::<&()>).await
+    }
+
+    pub async fn rest(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "rest", None::<&()>).await
+    }
+
+    pub async fn use_item(
+        &self,
+        name: &str,
+        body: &SimpleItemRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "use", Some(body)).await
+    }
+
+    pub async fn crafting(&self, name: &str, body: &CraftingRequest) -> Result<ActionData, Error> {
+        self.character_action(name, "crafting", Some(body)).await
+    }
+
+    pub async fn equip(&self, name: &str, body: &EquipRequest) -> Result<ActionData, Error> {
+        self.character_action(name, "equip", Some(body)).await
+    }
+
+    pub async fn unequip(&self, name: &str, body: &UnequipRequest) -> Result<ActionData, Error> {
+        self.character_action(name, "unequip", Some(body)).await
+    }
+
+    pub async fn bank_deposit(
+        &self,
+        name: &str,
+        body: &SimpleItemRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "bank/deposit", Some(body))
+            .await
+    }
+
+    pub async fn bank_deposit_gold(
+        &self,
+        name: &str,
+        body: &GoldRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "bank/deposit/gold", Some(body))
+            .await
+    }
+
+    pub async fn bank_withdraw(
+        &self,
+        name: &str,
+        body: &SimpleItemRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "bank/withdraw", Some(body))
+            .await
+    }
+
+    pub async fn bank_withdraw_gold(
+        &self,
+        name: &str,
+        body: &GoldRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "bank/withdraw/gold", Some(body))
+            .await
+    }
+
+    pub async fn bank_buy_expansion(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "bank/buy_expansion", None::<&()>)
+            .await
+    }
+
+    pub async fn recycling(
+        &self,
+        name: &str,
+        body: &SimpleItemRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "recycling", Some(body)).await
+    }
+
+    pub async fn ge_buy(
+        &self,
+        name: &str,
+        body: &GETransactionRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "ge/buy", Some(body)).await
+    }
+
+    pub async fn ge_sell(
+        &self,
+        name: &str,
+        body: &GETransactionRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "ge/sell", Some(body)).await
+    }
+
+    pub async fn task_new(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "task/new", None::<&()>).await
+    }
+
+    pub async fn task_complete(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "task/complete", None::<&()>)
+            .await
+    }
+
+    pub async fn task_exchange(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "task/exchange", None::<&()>)
+            .await
+    }
+
+    pub async fn task_cancel(&self, name: &str) -> Result<ActionData, Error> {
+        self.character_action(name, "task/cancel", None::<&()>)
+            .await
+    }
+
+    pub async fn task_trade(
+        &self,
+        name: &str,
+        body: &SimpleItemRequest,
+    ) -> Result<ActionData, Error> {
+        self.character_action(name, "task/trade", Some(body)).await
+    }
+
+    // Bank
+
+    pub async fn bank_items(&self, page: &Page) -> Result<Paginated<SimpleItem>, Error> {
+        self.get_page("my/bank/items", page).await
+    }
+
+    pub async fn all_bank_items(&self) -> Result<Vec<SimpleItem>, Error> {
+        self.get_all("my/bank/items").await
+    }
+
+    pub async fn bank_details(&self) -> Result<BankDetails, Error> {
+        self.get("my/bank").await
+    }
+
+    // Maps
+
+    pub async fn maps(&self, page: &Page) -> Result<Paginated<Map>, Error> {
+        self.get_page("maps/", page).await
+    }
+
+    pub async fn all_maps(&self) -> Result<Vec<Map>, Error> {
+        self.get_all("maps/").await
+    }
+
+    pub async fn map(&self, x: i64, y: i64) -> Result<Map, Error> {
+        self.get(&format!("maps/{x}/{y}")).await
+    }
+
+    // Items
+
+    pub async fn items(&self, page: &Page) -> Result<Paginated<Item>, Error> {
+        self.get_page("items/", page).await
+    }
+
+    pub async fn all_items(&self) -> Result<Vec<Item>, Error> {
+        self.get_all("items/").await
+    }
+
+    pub async fn item(&self, code: &str) -> Result<SingleItem, Error> {
+        self.get(&format!("items/{code}")).await
+    }
+
+    // Monsters
+
+    pub async fn monsters(&self, page: &Page) -> Result<Paginated<Monster>, Error> {
+        self.get_page("monsters/", page).await
+    }
+
+    pub async fn all_monsters(&self) -> Result<Vec<Monster>, Error> {
+        self.get_all("monsters/").await
+    }
+
+    pub async fn monster(&self, code: &str) -> Result<Monster, Error> {
+        self.get(&format!("monsters/{code}")).await
+    }
+
+    // Resources
+
+    pub async fn resources(&self, page: &Page) -> Result<Paginated<Resource>, Error> {
+        self.get_page("resources/", page).await
+    }
+
+    pub async fn all_resources(&self) -> Result<Vec<Resource>, Error> {
+        self.get_all("resources/").await
+    }
+
+    pub async fn resource(&self, code: &str) -> Result<Resource, Error> {
+        self.get(&format!("resources/{code}")).await
+    }
+
+    // Events
+
+    pub async fn events(&self, page: &Page) -> Result<Paginated<Event>, Error> {
+        self.get_page("events/", page).await
+    }
+
+    // Grand Exchange
+
+    pub async fn all_ge_items(&self) -> Result<Vec<GEItem>, Error> {
+        self.get_all("ge/").await
+    }
+
+    pub async fn ge_items(&self, page: &Page) -> Result<Paginated<GEItem>, Error> {
+        self.get_page("ge/", page).await
+    }
+
+    pub async fn ge_item(&self, code: &str) -> Result<GEItem, Error> {
+        self.get(&format!("ge/{code}")).await
+    }
+
+    // Tasks
+
+    pub async fn tasks(&self, page: &Page) -> Result<Paginated<TaskDefinition>, Error> {
+        self.get_page("tasks/list", page).await
+    }
+
+    pub async fn task(&self, code: &str) -> Result<TaskDefinition, Error> {
+        self.get(&format!("tasks/list/{code}")).await
+    }
+
+    pub async fn task_rewards(
+        &self,
+        page: &Page,
+    ) -> Result<Paginated<TaskRewardDefinition>, Error> {
+        self.get_page("tasks/rewards", page).await
+    }
+
+    pub async fn task_reward(&self, code: &str) -> Result<TaskRewardDefinition, Error> {
+        self.get(&format!("tasks/rewards/{code}")).await
+    }
+
+    // Achievements
+
+    pub async fn achievements(&self, page: &Page) -> Result<Paginated<Achievement>, Error> {
+        self.get_page("achievements", page).await
+    }
+
+    pub async fn achievement(&self, code: &str) -> Result<Achievement, Error> {
+        self.get(&format!("achievements/{code}")).await
+    }
+
+    // Leaderboard
+
+    pub async fn leaderboard(&self, page: &Page) -> Result<Paginated<LeaderboardEntry>, Error> {
+        self.get_page("leaderboard/characters", page).await
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::time::Instant;
+
+/// Server time estimated from a monotonic local clock and the offset to the server clock
+/// measured from API responses.
+///
+/// Every response narrows down the range the offset can be in: the server time reported in
+/// the response was taken at some point between sending the request and receiving the
+/// response, and is truncated to the resolution of the `Date` header. The lower end of the
+/// range is used so that sleeping until a cooldown expiration never wakes up too early.
+#[derive(Clone)]
+pub struct Clock(Arc<Inner>);
+
+struct Inner {
+    anchor: DateTime<Utc>,
+    anchor_instant: Instant,
+    // lower and upper bound of the server clock offset in milliseconds
+    skew: Mutex<Option<(i64, i64)>>,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Self(Arc::new(Inner {
+            anchor: Utc::now(),
+            anchor_instant: Instant::now(),
+            skew: Mutex::new(None),
+        }))
+    }
+
+    fn local_time(&self, instant: Instant) -> DateTime<Utc> {
+        let elapsed = instant.saturating_duration_since(self.0.anchor_instant);
+
+        chrono::Duration::from_std(elapsed)
+            .ok()
+            .and_then(|elapsed| self.0.anchor.checked_add_signed(elapsed))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC)
+    }
+
+    /// Record the server time reported by a response to a request sent at `sent` and
+    /// received at `received`, truncated to `resolution`
+    pub fn observe(
+        &self,
+        sent: Instant,
+        received: Instant,
+        server_time: DateTime<Utc>,
+        resolution: chrono::Duration,
+    ) {
+        let lower = (server_time - self.local_time(received)).num_milliseconds();
+        let upper = (server_time + resolution - self.local_time(sent)).num_milliseconds();
+
+        let mut skew = self.0.skew.lock().expect("clock lock poisoned");
+
+        *skew = match *skew {
+            Some((l, u)) if lower.max(l) <= upper.min(u) => Some((lower.max(l), upper.min(u))),
+            // first measurement, or the clocks have drifted apart so start over
+            _ => Some((lower, upper)),
+        };
+    }
+
+    /// Conservative estimate of the offset from the local clock to the server clock
+    pub fn skew(&self) -> chrono::Duration {
+        let skew = self.0.skew.lock().expect("clock lock poisoned");
+
+        chrono::Duration::milliseconds(skew.map(|(lower, _)| lower).unwrap_or_default())
+    }
+
+    /// Estimated current server time
+    pub fn now(&self) -> DateTime<Utc> {
+        self.local_time(Instant::now()) + self.skew()
+    }
+
+    /// Time left until the server clock reaches `time`
+    pub fn until(&self, time: DateTime<Utc>) -> Duration {
+        (time - self.now()).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    pub async fn sleep_until(&self, time: DateTime<Utc>) {
+        tokio::time::sleep(self.until(time)).await;
+    }
+}
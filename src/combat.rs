@@ -0,0 +1,168 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::response::{Character, Monster};
+
+// Fights that last longer than this are lost
+const MAX_TURNS: i64 = 100;
+
+// Number of fights simulated to predict an outcome
+const SAMPLES: u32 = 1000;
+
+/// Stats of one side of a fight, per element in the order fire, earth, water, air
+struct Fighter {
+    hp: i64,
+    attack: [i64; 4],
+    dmg: [i64; 4],
+    res: [i64; 4],
+    critical_strike: i64,
+}
+
+impl Fighter {
+    fn character(character: &Character) -> Self {
+        Self {
+            hp: character.hp,
+            attack: [
+                character.attack_fire,
+                character.attack_earth,
+                character.attack_water,
+                character.attack_air,
+            ],
+            dmg: [
+                character.dmg_fire,
+                character.dmg_earth,
+                character.dmg_water,
+                character.dmg_air,
+            ],
+            res: [
+                character.res_fire,
+                character.res_earth,
+                character.res_water,
+                character.res_air,
+            ],
+            critical_strike: character.critical_strike,
+        }
+    }
+
+    fn monster(monster: &Monster) -> Self {
+        Self {
+            hp: monster.hp,
+            attack: [
+                monster.attack_fire,
+                monster.attack_earth,
+                monster.attack_water,
+                monster.attack_air,
+            ],
+            dmg: [0; 4],
+            res: [
+                monster.res_fire,
+                monster.res_earth,
+                monster.res_water,
+                monster.res_air,
+            ],
+            critical_strike: 0,
+        }
+    }
+
+    /// Damage dealt by one turn of attacks. Each element's hit is reduced by the defender's
+    /// resistance and blocked with a chance of a tenth of it, and the whole turn is a
+    /// critical strike for 50% more damage with a chance of `critical_strike` percent.
+    fn attack(&self, defender: &Fighter, rng: &mut impl Rng) -> i64 {
+        let damage: i64 = (0..4)
+            .filter(|&i| self.attack[i] > 0)
+            .filter(|&i| !rng.gen_bool((defender.res[i] as f64 / 1000.0).clamp(0.0, 1.0)))
+            .map(|i| {
+                let hit = self.attack[i] as f64 * (1.0 + self.dmg[i] as f64 / 100.0);
+                (hit * (1.0 - defender.res[i] as f64 / 100.0)).round() as i64
+            })
+            .sum();
+
+        if rng.gen_bool((self.critical_strike as f64 / 100.0).clamp(0.0, 1.0)) {
+            damage + damage / 2
+        } else {
+            damage
+        }
+    }
+}
+
+/// How a single fight ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    pub win: bool,
+    pub turns: i64,
+    /// The character's hp at the end of the fight
+    pub hp: i64,
+}
+
+/// Fight turn by turn, the character attacking first, with crits and blocks rolled by `rng`
+pub fn fight(character: &Character, monster: &Monster, rng: &mut impl Rng) -> Outcome {
+    let player = Fighter::character(character);
+    let monster = Fighter::monster(monster);
+
+    let mut hp = player.hp;
+    let mut monster_hp = monster.hp;
+
+    for turn in 1..=MAX_TURNS {
+        if turn % 2 == 1 {
+            monster_hp -= player.attack(&monster, rng);
+            if monster_hp <= 0 {
+                return Outcome {
+                    win: true,
+                    turns: turn,
+                    hp,
+                };
+            }
+        } else {
+            hp -= monster.attack(&player, rng);
+            if hp <= 0 {
+                return Outcome {
+                    win: false,
+                    turns: turn,
+                    hp: 0,
+                };
+            }
+        }
+    }
+
+    Outcome {
+        win: false,
+        turns: MAX_TURNS,
+        hp,
+    }
+}
+
+/// Expected outcome of fighting a monster
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prediction {
+    pub win_probability: f64,
+    /// Average hp lost over won and lost fights
+    pub hp_loss: f64,
+}
+
+impl Prediction {
+    /// Whether the character is more likely to win than to lose
+    pub fn is_win(&self) -> bool {
+        self.win_probability >= 0.5
+    }
+}
+
+/// Predict the outcome of a fight from the character's current stats by simulating it many
+/// times. The simulations are seeded, so the same stats always give the same prediction.
+pub fn predict(character: &Character, monster: &Monster) -> Prediction {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut wins = 0;
+    let mut hp_loss = 0;
+    for _ in 0..SAMPLES {
+        let outcome = fight(character, monster, &mut rng);
+
+        if outcome.win {
+            wins += 1;
+        }
+        hp_loss += character.hp - outcome.hp;
+    }
+
+    Prediction {
+        win_probability: wins as f64 / SAMPLES as f64,
+        hp_loss: hp_loss as f64 / SAMPLES as f64,
+    }
+}
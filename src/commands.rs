@@ -0,0 +1,161 @@
+use std::error::Error;
+
+use artifacts::{
+    bank::Bank,
+    client::Client,
+    config::Task,
+    response::{ActionData, FightResult},
+    tasks, Character,
+};
+
+pub fn status(character: &Character) -> Result<(), Box<dyn Error>> {
+    let state = character.state();
+
+    println!(
+        "{} level {} ({}/{} xp)",
+        state.name, state.level, state.xp, state.max_xp
+    );
+    println!("hp {}/{}, gold {}", state.hp, state.max_hp, state.gold);
+    println!("at ({}, {})", state.x, state.y);
+
+    if let Some(expiration) = state.cooldown_expiration {
+        let remaining = character.client().clock().until(expiration);
+        if !remaining.is_zero() {
+            println!("cooldown {:.1}s", remaining.as_secs_f64());
+        }
+    }
+
+    if !state.task.is_empty() {
+        println!(
+            "task {} {} ({}/{})",
+            state.task_type, state.task, state.task_progress, state.task_total
+        );
+    }
+
+    let inventory = &state.inventory;
+    println!(
+        "inventory {}/{} items, {} free slots",
+        inventory.total_items(),
+        inventory.max_items(),
+        inventory.free_slots()
+    );
+    for slot in inventory.items() {
+        println!("  {} {}", slot.quantity, slot.code);
+    }
+
+    Ok(())
+}
+
+pub async fn move_to(character: &Character, x: i32, y: i32) -> Result<(), Box<dyn Error>> {
+    let task = Task::Move { x, y };
+    let data = character.move_to(&task, x, y).await?.await?;
+
+    println!("moved to ({}, {})", data.character.x, data.character.y);
+    print_cooldown(&data);
+
+    Ok(())
+}
+
+pub async fn fight(character: &Character) -> Result<(), Box<dyn Error>> {
+    let task = Task::Fight {
+        monster: tile_content(character).await?,
+        times: 1,
+        position: None,
+    };
+    let data = character.fight(&task).await?.await?;
+
+    if let Some(fight) = &data.fight {
+        let result = match fight.result {
+            FightResult::Win => "won",
+            FightResult::Lose => "lost",
+        };
+        println!(
+            "{result} in {} turns, {} xp, {} gold",
+            fight.turns, fight.xp, fight.gold
+        );
+        for item in &fight.drops {
+            println!("  {} {}", item.quantity, item.code);
+        }
+    }
+    print_cooldown(&data);
+
+    Ok(())
+}
+
+pub async fn gather(character: &Character) -> Result<(), Box<dyn Error>> {
+    let task = Task::Gather {
+        resource: tile_content(character).await?,
+        times: 1,
+        position: None,
+    };
+    let data = character.gathering(&task).await?.await?;
+
+    if let Some(details) = &data.details {
+        println!("{} xp", details.xp);
+        for item in &details.items {
+            println!("  {} {}", item.quantity, item.code);
+        }
+    }
+    print_cooldown(&data);
+
+    Ok(())
+}
+
+pub async fn craft(character: &Character, code: &str, quantity: i64) -> Result<(), Box<dyn Error>> {
+    let task = Task::Craft {
+        item: code.into(),
+        quantity,
+        position: None,
+    };
+    let data = character.crafting(&task, code, quantity).await?.await?;
+
+    if let Some(details) = &data.details {
+        println!("{} xp", details.xp);
+        for item in &details.items {
+            println!("  {} {}", item.quantity, item.code);
+        }
+    }
+    print_cooldown(&data);
+
+    Ok(())
+}
+
+pub async fn deposit_all(character: &Character) -> Result<(), Box<dyn Error>> {
+    tasks::deposit_inventory(character).await?;
+
+    println!("deposited the inventory");
+
+    Ok(())
+}
+
+pub async fn bank_list(client: &Client) -> Result<(), Box<dyn Error>> {
+    let bank = Bank::new(client);
+
+    let details = bank.details().await?;
+    println!("{} gold, {} slots", details.gold, details.slots);
+
+    let mut items: Vec<_> = bank.items().await?.into_iter().collect();
+    items.sort();
+    for (code, quantity) in items {
+        println!("  {quantity} {code}");
+    }
+
+    Ok(())
+}
+
+// Code of the content on the character's tile, empty if there is none
+async fn tile_content(character: &Character) -> Result<String, Box<dyn Error>> {
+    let position = character.read_state(|state| (state.x, state.y));
+    let tiles = character.game().map.tiles().await?;
+
+    Ok(tiles
+        .iter()
+        .find(|tile| (tile.x, tile.y) == position)
+        .and_then(|tile| tile.content.as_ref())
+        .map(|content| content.code.clone())
+        .unwrap_or_default())
+}
+
+fn print_cooldown(data: &ActionData) {
+    println!("cooldown {}s", data.cooldown.total_seconds);
+}
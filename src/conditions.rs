@@ -1,13 +1,45 @@
-use crate::{character::Character, config::TaskName, error::Error};
+use crate::{
+    config::{Condition, Skill},
+    response::Character,
+};
 
-// True if the character has a full inventory
-pub async fn full_inventory(character: &Character, current_task: TaskName) -> Result<bool, Error> {
-    let (max_items, inventory) = character.inventory(current_task).await?;
+/// True if `condition` holds for the character state
+pub fn evaluate(condition: &Condition, character: &Character) -> bool {
+    match condition {
+        Condition::All(conditions) => conditions.iter().all(|c| evaluate(c, character)),
+        Condition::Any(conditions) => conditions.iter().any(|c| evaluate(c, character)),
+        Condition::Not(condition) => !evaluate(condition, character),
+        Condition::FullInventory => full_inventory(character),
+        Condition::HpBelow(hp) => character.hp < *hp,
+        Condition::ItemCountAtLeast { code, n } => character.inventory.count(code) >= *n,
+        Condition::SkillLevelAtLeast { skill, level } => skill_level(character, *skill) >= *level,
+        Condition::InventoryFreeSlotsBelow(n) => character.inventory.free_slots() < *n,
+        Condition::GoldAtLeast(gold) => character.gold >= *gold,
+        Condition::TaskProgressComplete => {
+            character.task_total > 0 && character.task_progress >= character.task_total
+        }
+    }
+}
 
-    if inventory.iter().map(|slot| slot.quantity).sum::<i64>() < max_items {
+// True if the character has a full inventory
+pub fn full_inventory(character: &Character) -> bool {
+    if !character.inventory.is_full() {
         tracing::debug!("Inventory is not full");
-        return Ok(false);
+        return false;
     }
 
-    Ok(true)
+    true
+}
+
+pub fn skill_level(character: &Character, skill: Skill) -> i64 {
+    match skill {
+        Skill::Combat => character.level,
+        Skill::Mining => character.mining_level,
+        Skill::Woodcutting => character.woodcutting_level,
+        Skill::Fishing => character.fishing_level,
+        Skill::Weaponcrafting => character.weaponcrafting_level,
+        Skill::Gearcrafting => character.gearcrafting_level,
+        Skill::Jewelrycrafting => character.jewelrycrafting_level,
+        Skill::Cooking => character.cooking_level,
+    }
 }
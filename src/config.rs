@@ -1,24 +1,310 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
 use serde::Deserialize;
+use thiserror::Error;
+
+use crate::client::DEFAULT_API_URL;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("toml error: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[error("character {0} is configured more than once")]
+    DuplicateCharacter(String),
+}
 
 #[derive(Deserialize)]
 pub struct Config {
+    /// Base URL of the API, override to use a local mock server or another world
+    #[serde(default = "default_api_url")]
+    pub api_url: String,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    #[serde(default)]
+    pub heal: HealPolicy,
+    /// What characters deposit in the bank, unless overridden per character
+    #[serde(default)]
+    pub deposit: DepositPolicy,
+    /// Serve Prometheus metrics if set
+    pub metrics: Option<MetricsConfig>,
+    /// Log every executed action if set
+    pub history: Option<HistoryConfig>,
     pub characters: Vec<CharacterConfig>,
 }
 
+impl Config {
+    /// Read, parse and validate the config at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse and validate a config
+    pub fn parse(source: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(source)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut names = HashSet::new();
+        for character in &self.characters {
+            if !names.insert(&character.name) {
+                return Err(ConfigError::DuplicateCharacter(character.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reloads a config file whenever its modification time changes
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// Watch `path`, ignoring its current contents
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let modified = modified(&path);
+
+        Self { path, modified }
+    }
+
+    /// The reloaded config and its source if the file changed since the last poll
+    pub fn poll(&mut self) -> Option<Result<(Config, String), ConfigError>> {
+        let modified = modified(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+        Some(
+            std::fs::read_to_string(&self.path)
+                .map_err(ConfigError::from)
+                .and_then(|source| Ok((Config::parse(&source)?, source))),
+        )
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn default_api_url() -> String {
+    DEFAULT_API_URL.into()
+}
+
+/// When characters heal between fights: once hp drops below `below_hp_percent` of max hp
+/// they eat food from their inventory, if `eat_food` is set, and rest for whatever is left
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct HealPolicy {
+    pub below_hp_percent: f64,
+    pub eat_food: bool,
+}
+
+impl Default for HealPolicy {
+    fn default() -> Self {
+        Self {
+            below_hp_percent: 50.0,
+            eat_food: true,
+        }
+    }
+}
+
+impl HealPolicy {
+    /// Whether a character with `hp` out of `max_hp` should heal
+    pub fn should_heal(&self, hp: i64, max_hp: i64) -> bool {
+        (hp as f64) < max_hp as f64 * self.below_hp_percent / 100.0
+    }
+}
+
+/// What `DepositInventory` leaves in the bank: every item except the quantities in `keep`,
+/// only the items in `only` if it isn't empty, and gold above `gold_above` if it is set
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct DepositPolicy {
+    pub keep: HashMap<String, i64>,
+    pub only: Vec<String>,
+    pub gold_above: Option<i64>,
+}
+
+impl DepositPolicy {
+    /// How many of the `quantity` of `code` in the inventory to deposit
+    pub fn items_to_deposit(&self, code: &str, quantity: i64) -> i64 {
+        if !self.only.is_empty() && !self.only.iter().any(|only| only == code) {
+            return 0;
+        }
+
+        (quantity - self.keep.get(code).copied().unwrap_or_default()).max(0)
+    }
+
+    /// How much of the character's `gold` to deposit
+    pub fn gold_to_deposit(&self, gold: i64) -> i64 {
+        match self.gold_above {
+            Some(above) => (gold - above).max(0),
+            None => 0,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MetricsConfig {
+    /// Address of the `/metrics` endpoint, e.g. `127.0.0.1:9100`
+    pub listen: String,
+}
+
+/// Where executed actions are logged as JSON lines, and when the file is rotated
+#[derive(Deserialize, Debug, Clone)]
+pub struct HistoryConfig {
+    pub path: PathBuf,
+    /// Size the file is rotated at
+    #[serde(default = "default_history_max_bytes")]
+    pub max_bytes: u64,
+    /// Number of rotated files kept
+    #[serde(default = "default_history_keep")]
+    pub keep: u32,
+}
+
+fn default_history_max_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_history_keep() -> u32 {
+    5
+}
+
+/// How long the supervisor waits before restarting a character's task loop after a failure,
+/// doubling (by default) with each consecutive failure up to a maximum
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub initial_backoff_secs: f64,
+    pub max_backoff_secs: f64,
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff_secs: 5.0,
+            max_backoff_secs: 300.0,
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff after `failures` consecutive failures
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let secs =
+            self.initial_backoff_secs * self.multiplier.powi(failures.saturating_sub(1) as i32);
+
+        Duration::from_secs_f64(secs.clamp(0.0, self.max_backoff_secs))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct CharacterConfig {
     pub name: String,
+    /// Token for this character's account, falls back to `ARTIFACTS_TOKEN`
+    pub token: Option<String>,
+    /// Overrides the deposit policy for this character
+    pub deposit: Option<DepositPolicy>,
     pub tasks: Vec<TaskConfig>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct TaskConfig {
-    pub name: TaskName,
+    #[serde(flatten)]
+    pub task: Task,
     pub condition: Option<Condition>,
 }
 
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// A task and its arguments, e.g. `{ kind = "Gather", resource = "iron_rocks", times = 20 }`.
+///
+/// Tasks with an optional `position` move there first, otherwise they move to the nearest
+/// tile with the content they need.
 #[derive(Deserialize, Debug, Clone)]
-pub enum TaskName {
+#[serde(tag = "kind")]
+pub enum Task {
+    Gather {
+        resource: String,
+        times: u32,
+        position: Option<Position>,
+    },
+    Fight {
+        monster: String,
+        times: u32,
+        position: Option<Position>,
+    },
+    Craft {
+        item: String,
+        quantity: i64,
+        position: Option<Position>,
+    },
+    /// Obtain `quantity` of an item, withdrawing, gathering and crafting everything needed
+    Produce {
+        item: String,
+        quantity: i64,
+    },
+    Move {
+        x: i32,
+        y: i32,
+    },
+    /// Deposit an item in the bank, everything in the inventory if `quantity` is omitted
+    Deposit {
+        code: String,
+        quantity: Option<i64>,
+        position: Option<Position>,
+    },
+    Withdraw {
+        code: String,
+        quantity: i64,
+        position: Option<Position>,
+    },
+    /// Sell everything but `keep` of an item on the Grand Exchange, if it sells for at least
+    /// `min_price` each
+    Sell {
+        code: String,
+        min_price: i64,
+        #[serde(default)]
+        keep: i64,
+        position: Option<Position>,
+    },
+    /// Buy `quantity` of an item on the Grand Exchange, if it costs at most `max_price` each
+    Buy {
+        code: String,
+        max_price: i64,
+        quantity: i64,
+        position: Option<Position>,
+    },
+    /// Accept a task from the tasks master of `task_type`, work it to completion and turn it
+    /// in for a tasks coin
+    TaskMaster {
+        #[serde(default)]
+        task_type: TaskType,
+    },
+    /// Equip the best gear in the inventory and bank for `activity`, withdrawing it first if
+    /// needed
+    EquipBest {
+        activity: Activity,
+    },
     CopperIngots,
     KillChickens,
     DepositInventory,
@@ -28,7 +314,71 @@ pub enum TaskName {
     MineIron,
 }
 
-#[derive(Deserialize, Debug)]
+/// What gear is chosen for, e.g. `{ Fight = "chicken" }` or `"Mining"`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Activity {
+    Fight(String),
+    Mining,
+    Woodcutting,
+    Fishing,
+}
+
+impl Activity {
+    /// The gathering skill, `None` for fights
+    pub fn skill(&self) -> Option<&'static str> {
+        match self {
+            Activity::Fight(_) => None,
+            Activity::Mining => Some("mining"),
+            Activity::Woodcutting => Some("woodcutting"),
+            Activity::Fishing => Some("fishing"),
+        }
+    }
+}
+
+/// The kind of tasks a tasks master gives, which is also its tile's content code
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskType {
+    #[default]
+    Monsters,
+    Items,
+}
+
+impl TaskType {
+    pub fn code(&self) -> &'static str {
+        match self {
+            TaskType::Monsters => "monsters",
+            TaskType::Items => "items",
+        }
+    }
+}
+
+/// A condition on the character state, e.g. `"FullInventory"`, `{ HpBelow = 50 }` or
+/// `{ All = [{ GoldAtLeast = 100 }, { Not = "FullInventory" }] }`
+#[derive(Deserialize, Debug, Clone)]
 pub enum Condition {
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+    Not(Box<Condition>),
     FullInventory,
+    HpBelow(i64),
+    ItemCountAtLeast { code: String, n: i64 },
+    SkillLevelAtLeast { skill: Skill, level: i64 },
+    InventoryFreeSlotsBelow(i64),
+    GoldAtLeast(i64),
+    TaskProgressComplete,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Skill {
+    /// The character level
+    Combat,
+    Mining,
+    Woodcutting,
+    Fishing,
+    Weaponcrafting,
+    Gearcrafting,
+    Jewelrycrafting,
+    Cooking,
 }
@@ -0,0 +1,283 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{
+    combat::{self, Prediction},
+    config::Activity,
+    error::Error,
+    response::{Character, Item, Monster},
+};
+
+/// A gear slot of a character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Weapon,
+    Shield,
+    Helmet,
+    BodyArmor,
+    LegArmor,
+    Boots,
+    Ring1,
+    Ring2,
+    Amulet,
+    Artifact1,
+    Artifact2,
+    Artifact3,
+    Consumable1,
+    Consumable2,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 14] = [
+        Slot::Weapon,
+        Slot::Shield,
+        Slot::Helmet,
+        Slot::BodyArmor,
+        Slot::LegArmor,
+        Slot::Boots,
+        Slot::Ring1,
+        Slot::Ring2,
+        Slot::Amulet,
+        Slot::Artifact1,
+        Slot::Artifact2,
+        Slot::Artifact3,
+        Slot::Consumable1,
+        Slot::Consumable2,
+    ];
+
+    /// The slot's name in equip and unequip requests
+    pub fn code(&self) -> &'static str {
+        match self {
+            Slot::Weapon => "weapon",
+            Slot::Shield => "shield",
+            Slot::Helmet => "helmet",
+            Slot::BodyArmor => "body_armor",
+            Slot::LegArmor => "leg_armor",
+            Slot::Boots => "boots",
+            Slot::Ring1 => "ring1",
+            Slot::Ring2 => "ring2",
+            Slot::Amulet => "amulet",
+            Slot::Artifact1 => "artifact1",
+            Slot::Artifact2 => "artifact2",
+            Slot::Artifact3 => "artifact3",
+            Slot::Consumable1 => "consumable1",
+            Slot::Consumable2 => "consumable2",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Slot> {
+        Slot::ALL.into_iter().find(|slot| slot.code() == code)
+    }
+
+    /// The item type that can be equipped in the slot
+    pub fn item_type(&self) -> &'static str {
+        match self {
+            Slot::Ring1 | Slot::Ring2 => "ring",
+            Slot::Artifact1 | Slot::Artifact2 | Slot::Artifact3 => "artifact",
+            Slot::Consumable1 | Slot::Consumable2 => "consumable",
+            slot => slot.code(),
+        }
+    }
+
+    /// Code of the item equipped in the slot, empty if there is none
+    pub fn equipped<'a>(&self, character: &'a Character) -> &'a str {
+        match self {
+            Slot::Weapon => &character.weapon_slot,
+            Slot::Shield => &character.shield_slot,
+            Slot::Helmet => &character.helmet_slot,
+            Slot::BodyArmor => &character.body_armor_slot,
+            Slot::LegArmor => &character.leg_armor_slot,
+            Slot::Boots => &character.boots_slot,
+            Slot::Ring1 => &character.ring1_slot,
+            Slot::Ring2 => &character.ring2_slot,
+            Slot::Amulet => &character.amulet_slot,
+            Slot::Artifact1 => &character.artifact1_slot,
+            Slot::Artifact2 => &character.artifact2_slot,
+            Slot::Artifact3 => &character.artifact3_slot,
+            Slot::Consumable1 => &character.consumable1_slot,
+            Slot::Consumable2 => &character.consumable2_slot,
+        }
+    }
+
+    pub fn equipped_mut<'a>(&self, character: &'a mut Character) -> &'a mut String {
+        match self {
+            Slot::Weapon => &mut character.weapon_slot,
+            Slot::Shield => &mut character.shield_slot,
+            Slot::Helmet => &mut character.helmet_slot,
+            Slot::BodyArmor => &mut character.body_armor_slot,
+            Slot::LegArmor => &mut character.leg_armor_slot,
+            Slot::Boots => &mut character.boots_slot,
+            Slot::Ring1 => &mut character.ring1_slot,
+            Slot::Ring2 => &mut character.ring2_slot,
+            Slot::Amulet => &mut character.amulet_slot,
+            Slot::Artifact1 => &mut character.artifact1_slot,
+            Slot::Artifact2 => &mut character.artifact2_slot,
+            Slot::Artifact3 => &mut character.artifact3_slot,
+            Slot::Consumable1 => &mut character.consumable1_slot,
+            Slot::Consumable2 => &mut character.consumable2_slot,
+        }
+    }
+}
+
+fn stat<'a>(character: &'a mut Character, name: &str) -> Option<&'a mut i64> {
+    Some(match name {
+        "hp" => &mut character.max_hp,
+        "haste" => &mut character.haste,
+        "critical_strike" => &mut character.critical_strike,
+        "attack_fire" => &mut character.attack_fire,
+        "attack_earth" => &mut character.attack_earth,
+        "attack_water" => &mut character.attack_water,
+        "attack_air" => &mut character.attack_air,
+        "dmg_fire" => &mut character.dmg_fire,
+        "dmg_earth" => &mut character.dmg_earth,
+        "dmg_water" => &mut character.dmg_water,
+        "dmg_air" => &mut character.dmg_air,
+        "res_fire" => &mut character.res_fire,
+        "res_earth" => &mut character.res_earth,
+        "res_water" => &mut character.res_water,
+        "res_air" => &mut character.res_air,
+        _ => return None,
+    })
+}
+
+/// Add (`sign` 1) or remove (`sign` -1) the stats of an item
+pub fn apply_effects(character: &mut Character, item: &Item, sign: i64) {
+    for effect in &item.effects {
+        if let Some(stat) = stat(character, &effect.name) {
+            *stat += sign * effect.value;
+        }
+    }
+
+    character.hp = character.hp.min(character.max_hp);
+}
+
+/// An item to equip in place of whatever is in the slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub slot: Slot,
+    pub code: String,
+    /// Whether it has to be withdrawn from the bank first
+    pub from_bank: bool,
+}
+
+/// How well gear suits an activity, higher is better
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Score {
+    Fight(Prediction),
+    /// Percentage the gathering cooldown is reduced by
+    Gather(i64),
+}
+
+impl Score {
+    fn is_better_than(&self, other: &Score) -> bool {
+        match (self, other) {
+            (Score::Fight(a), Score::Fight(b)) => {
+                a.win_probability > b.win_probability
+                    || (a.win_probability == b.win_probability && a.hp_loss < b.hp_loss)
+            }
+            (Score::Gather(a), Score::Gather(b)) => a > b,
+            _ => false,
+        }
+    }
+}
+
+/// The gear in the inventory and bank that suits `activity` better than what the character
+/// has equipped, chosen slot by slot. Fighting gear is compared by predicting the fight at
+/// full hp, gathering gear by how much it shortens the skill's cooldown. Consumables are
+/// left alone.
+pub fn best_changes(
+    character: &Character,
+    items: &HashMap<String, Item>,
+    inventory: &HashMap<String, i64>,
+    bank: &HashMap<String, i64>,
+    activity: &Activity,
+    monsters: &[Monster],
+) -> Result<Vec<Change>, Error> {
+    let monster = match activity {
+        Activity::Fight(code) => match monsters.iter().find(|monster| &monster.code == code) {
+            Some(monster) => Some(monster),
+            None => return Err(Error::ContentNotFound(code.clone())),
+        },
+        _ => None,
+    };
+
+    let score = |character: &Character, slot: Slot| match (monster, activity.skill()) {
+        (Some(monster), _) => {
+            let rested = Character {
+                hp: character.max_hp,
+                ..character.clone()
+            };
+            Score::Fight(combat::predict(&rested, monster))
+        }
+        (None, skill) => Score::Gather(
+            items
+                .get(slot.equipped(character))
+                .into_iter()
+                .flat_map(|item| &item.effects)
+                .filter(|effect| Some(effect.name.as_str()) == skill)
+                .map(|effect| -effect.value)
+                .sum(),
+        ),
+    };
+
+    let mut character = character.clone();
+    let mut inventory = inventory.clone();
+    let mut bank = bank.clone();
+    let mut changes = vec![];
+
+    for slot in Slot::ALL {
+        if slot.item_type() == "consumable" {
+            continue;
+        }
+
+        let equipped = slot.equipped(&character).to_string();
+        let mut to_beat = score(&character, slot);
+        let mut best: Option<(Character, &Item)> = None;
+
+        // in a fixed order so ties always go the same way
+        let codes: BTreeSet<&String> = inventory
+            .iter()
+            .chain(&bank)
+            .filter(|(code, quantity)| **quantity > 0 && **code != equipped)
+            .map(|(code, _)| code)
+            .collect();
+
+        for code in codes {
+            let item = match items.get(code) {
+                Some(item) if item.kind == slot.item_type() && item.level <= character.level => {
+                    item
+                }
+                _ => continue,
+            };
+
+            let mut candidate = character.clone();
+            if let Some(current) = items.get(&equipped) {
+                apply_effects(&mut candidate, current, -1);
+            }
+            apply_effects(&mut candidate, item, 1);
+            *slot.equipped_mut(&mut candidate) = code.clone();
+
+            let candidate_score = score(&candidate, slot);
+            if candidate_score.is_better_than(&to_beat) {
+                to_beat = candidate_score;
+                best = Some((candidate, item));
+            }
+        }
+
+        if let Some((candidate, item)) = best {
+            let from_bank = inventory.get(&item.code).copied().unwrap_or_default() <= 0;
+            let available = if from_bank { &mut bank } else { &mut inventory };
+            if let Some(quantity) = available.get_mut(&item.code) {
+                *quantity -= 1;
+            }
+
+            changes.push(Change {
+                slot,
+                code: item.code.clone(),
+                from_bank,
+            });
+            character = candidate;
+        }
+    }
+
+    Ok(changes)
+}
@@ -1,15 +1,74 @@
 use thiserror::Error;
 
+use crate::response::ErrorData;
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
     #[error("api error: {:?}", ._0)]
-    APIError(#[from] crate::response::ErrorData),
+    APIError(ErrorData),
     #[error("invalid api response")]
     InvalidAPIResponse,
     #[error("serde json error: {0}")]
     SerdeJSON(#[from] serde_json::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("character in cooldown")]
     Cooldown,
+    #[error("character already at destination")]
+    AlreadyAtDestination,
+    #[error("missing item or insufficient quantity")]
+    MissingItem,
+    #[error("skill level too low")]
+    SkillLevelTooLow,
+    #[error("inventory full")]
+    InventoryFull,
+    #[error("character not found")]
+    CharacterNotFound,
+    #[error("nothing to do here on this map")]
+    NotFoundOnMap,
+    #[error("{0} not found on the map")]
+    ContentNotFound(String),
+    #[error("{0} can't be crafted")]
+    NotCraftable(String),
+    #[error("no way to obtain {0}")]
+    Unobtainable(String),
+    #[error("predicted to lose the fight against {0}")]
+    Unwinnable(String),
+    #[error("action was dropped before it was executed")]
+    ActionDropped,
+}
+
+impl Error {
+    /// The API error code, `None` for errors that didn't come from the API
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Error::APIError(error) => Some(error.code),
+            Error::MissingItem => Some(478),
+            Error::AlreadyAtDestination => Some(490),
+            Error::SkillLevelTooLow => Some(493),
+            Error::InventoryFull => Some(497),
+            Error::CharacterNotFound => Some(498),
+            Error::Cooldown => Some(499),
+            Error::NotFoundOnMap => Some(598),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorData> for Error {
+    fn from(error: ErrorData) -> Self {
+        match error.code {
+            478 => Error::MissingItem,
+            490 => Error::AlreadyAtDestination,
+            493 => Error::SkillLevelTooLow,
+            497 => Error::InventoryFull,
+            498 => Error::CharacterNotFound,
+            499 => Error::Cooldown,
+            598 => Error::NotFoundOnMap,
+            _ => Error::APIError(error),
+        }
+    }
 }
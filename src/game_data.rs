@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use tokio::sync::OnceCell;
+
+use crate::{
+    client::Client,
+    error::Error,
+    map::WorldMap,
+    response::{Item, Monster, Resource},
+};
+
+/// Game data shared by every character.
+///
+/// Items, resources and monsters don't change while the game is running, so they are
+/// fetched once when first used.
+pub struct GameData {
+    client: Client,
+    pub map: WorldMap,
+    items: OnceCell<HashMap<String, Item>>,
+    resources: OnceCell<Vec<Resource>>,
+    monsters: OnceCell<Vec<Monster>>,
+}
+
+impl GameData {
+    pub fn new(client: Client) -> Self {
+        Self {
+            map: WorldMap::new(client.clone()),
+            client,
+            items: OnceCell::new(),
+            resources: OnceCell::new(),
+            monsters: OnceCell::new(),
+        }
+    }
+
+    /// Every item by code
+    pub async fn items(&self) -> Result<&HashMap<String, Item>, Error> {
+        self.items
+            .get_or_try_init(|| async {
+                let items = self.client.all_items().await?;
+                tracing::debug!("Fetched {} items", items.len());
+
+                Ok(items
+                    .into_iter()
+                    .map(|item| (item.code.clone(), item))
+                    .collect())
+            })
+            .await
+    }
+
+    pub async fn item(&self, code: &str) -> Result<Option<&Item>, Error> {
+        Ok(self.items().await?.get(code))
+    }
+
+    pub async fn resources(&self) -> Result<&[Resource], Error> {
+        self.resources
+            .get_or_try_init(|| self.client.all_resources())
+            .await
+            .map(Vec::as_slice)
+    }
+
+    pub async fn monsters(&self) -> Result<&[Monster], Error> {
+        self.monsters
+            .get_or_try_init(|| self.client.all_monsters())
+            .await
+            .map(Vec::as_slice)
+    }
+}
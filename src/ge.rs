@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+
+use crate::{client::Client, error::Error, response::GEItem};
+
+/// Most items bought or sold in a single Grand Exchange transaction
+pub const MAX_TRANSACTION_QUANTITY: i64 = 50;
+
+/// Listings of the Grand Exchange. Prices and stock change with every trade, so nothing is
+/// cached.
+pub struct GrandExchange<'a> {
+    client: &'a Client,
+}
+
+impl<'a> GrandExchange<'a> {
+    pub fn new(client: &'a Client) -> Self {
+        Self { client }
+    }
+
+    /// Every listing by item code, fetching every page
+    pub async fn listings(&self) -> Result<HashMap<String, GEItem>, Error> {
+        Ok(self
+            .client
+            .all_ge_items()
+            .await?
+            .into_iter()
+            .map(|item| (item.code.clone(), item))
+            .collect())
+    }
+
+    /// The listing of a single item
+    pub async fn listing(&self, code: &str) -> Result<GEItem, Error> {
+        self.client.ge_item(code).await
+    }
+}
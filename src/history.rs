@@ -0,0 +1,203 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{
+    changes::Changes,
+    config::HistoryConfig,
+    error::Error,
+    request::Action,
+    response::{ActionData, Character},
+};
+
+/// One executed action, a line of the history
+#[derive(Debug, Serialize)]
+pub struct Entry<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub character: &'a str,
+    pub task: &'a str,
+    pub action: &'static str,
+    pub request: Option<Value>,
+    /// The HTTP status, which is the error code for actions the API rejected. `None` if there
+    /// was no response.
+    pub status: Option<i32>,
+    pub error: Option<String>,
+    pub cooldown_seconds: Option<i64>,
+    /// Changes from the state before the action, empty if it failed
+    #[serde(flatten)]
+    pub changes: Changes,
+}
+
+impl<'a> Entry<'a> {
+    /// The entry for an action executed at `timestamp`, `before` being the character state it
+    /// started from
+    pub fn new(
+        timestamp: DateTime<Utc>,
+        character: &'a str,
+        task: &'a str,
+        action: &Action,
+        before: &Character,
+        result: &Result<ActionData, Error>,
+    ) -> Self {
+        let (status, error, cooldown_seconds, changes) = match result {
+            Ok(data) => (
+                Some(200),
+                None,
+                Some(data.cooldown.total_seconds),
+                Changes::between(before, &data.character),
+            ),
+            Err(e) => (e.code(), Some(e.to_string()), None, Changes::default()),
+        };
+
+        Self {
+            timestamp,
+            character,
+            task,
+            action: action.path(),
+            request: action.body(),
+            status,
+            error,
+            cooldown_seconds,
+            changes,
+        }
+    }
+}
+
+enum Message {
+    Line(Vec<u8>),
+    /// Answered once every line sent before it has been written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Appends executed actions as JSON lines to a file, shared by every character. Once the file
+/// reaches `max_bytes` it is rotated to `<path>.1`, shifting older files up to `<path>.<keep>`.
+///
+/// The file is written and rotated on a thread of its own, so a slow disk doesn't hold up the
+/// characters' executors.
+#[derive(Clone)]
+pub struct History {
+    path: PathBuf,
+    sender: mpsc::UnboundedSender<Message>,
+}
+
+impl History {
+    pub fn open(config: &HistoryConfig) -> io::Result<Self> {
+        let path = config.path.clone();
+        let file = append(&path)?;
+        let size = file.metadata()?.len();
+
+        let writer = Writer {
+            path: path.clone(),
+            max_bytes: config.max_bytes,
+            keep: config.keep,
+            file,
+            size,
+        };
+        let (sender, receiver) = mpsc::unbounded_channel();
+        std::thread::Builder::new()
+            .name("history".into())
+            .spawn(move || writer.run(receiver))?;
+
+        Ok(Self { path, sender })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue `entry` to be appended to the file
+    pub fn record(&self, entry: &Entry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        self.sender.send(Message::Line(line)).map_err(stopped)
+    }
+
+    /// Wait until every entry recorded so far is in the file
+    pub async fn flush(&self) -> io::Result<()> {
+        let (done, written) = oneshot::channel();
+        self.sender.send(Message::Flush(done)).map_err(stopped)?;
+
+        written.await.map_err(stopped)
+    }
+
+    /// Path of the `n`th most recently rotated file
+    pub fn rotated(&self, n: u32) -> PathBuf {
+        rotated(&self.path, n)
+    }
+}
+
+struct Writer {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: u32,
+    file: File,
+    size: u64,
+}
+
+impl Writer {
+    /// Write lines until every `History` is dropped
+    fn run(mut self, mut receiver: mpsc::UnboundedReceiver<Message>) {
+        while let Some(message) = receiver.blocking_recv() {
+            match message {
+                Message::Line(line) => {
+                    if let Err(e) = self.write(&line) {
+                        tracing::warn!(error = %e, "failed to record action history");
+                    }
+                }
+                Message::Flush(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+
+    fn write(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+            self.file = append(&self.path)?;
+            self.size = 0;
+        }
+
+        self.file.write_all(line)?;
+        self.size += line.len() as u64;
+
+        Ok(())
+    }
+
+    fn rotate(&self) -> io::Result<()> {
+        if self.keep == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        for n in (1..self.keep).rev() {
+            let from = rotated(&self.path, n);
+            if from.exists() {
+                std::fs::rename(from, rotated(&self.path, n + 1))?;
+            }
+        }
+
+        std::fs::rename(&self.path, rotated(&self.path, 1))
+    }
+}
+
+fn rotated(path: &Path, n: u32) -> PathBuf {
+    let mut path = path.to_path_buf().into_os_string();
+    path.push(format!(".{n}"));
+    path.into()
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn stopped<E>(_: E) -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "history writer stopped")
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use serde::{de::Error as _, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// An inventory slot, empty when `code` is
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventorySlot {
+    pub slot: i64,
+    pub code: String,
+    pub quantity: i64,
+}
+
+/// A character's inventory: its slots and the max number of items across all of them.
+///
+/// Deserializes from the fields of a character, either the `inventory` array of slots or the
+/// legacy `inventory_slot1`, `inventory_slot1_quantity`, ... fields, along with
+/// `inventory_max_items`. Always serializes to the array.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Inventory {
+    slots: Vec<InventorySlot>,
+    max_items: i64,
+}
+
+impl Inventory {
+    /// An inventory with `slots` empty slots
+    pub fn new(slots: i64, max_items: i64) -> Self {
+        Self {
+            slots: (1..=slots)
+                .map(|slot| InventorySlot {
+                    slot,
+                    ..Default::default()
+                })
+                .collect(),
+            max_items,
+        }
+    }
+
+    /// Every slot, including empty ones
+    pub fn slots(&self) -> &[InventorySlot] {
+        &self.slots
+    }
+
+    /// The slots holding items
+    pub fn items(&self) -> impl Iterator<Item = &InventorySlot> {
+        self.slots
+            .iter()
+            .filter(|slot| !slot.code.is_empty() && slot.quantity > 0)
+    }
+
+    pub fn max_items(&self) -> i64 {
+        self.max_items
+    }
+
+    /// Quantity of an item
+    pub fn count(&self, code: &str) -> i64 {
+        self.items()
+            .filter(|slot| slot.code == code)
+            .map(|slot| slot.quantity)
+            .sum()
+    }
+
+    /// Number of items across all slots
+    pub fn total_items(&self) -> i64 {
+        self.items().map(|slot| slot.quantity).sum()
+    }
+
+    pub fn free_slots(&self) -> i64 {
+        self.slots
+            .iter()
+            .filter(|slot| slot.code.is_empty())
+            .count() as i64
+    }
+
+    /// Whether the inventory holds its max number of items
+    pub fn is_full(&self) -> bool {
+        self.total_items() >= self.max_items
+    }
+
+    /// Add items if they fit within the max number of items and a slot
+    pub fn add(&mut self, code: &str, quantity: i64) -> bool {
+        if self.total_items().saturating_add(quantity) > self.max_items {
+            return false;
+        }
+
+        self.insert(code, quantity)
+    }
+
+    /// Add items to the slot already holding the item or an empty one, regardless of the max
+    /// number of items
+    pub fn insert(&mut self, code: &str, quantity: i64) -> bool {
+        let slot = match self.slots.iter().position(|slot| slot.code == code) {
+            Some(i) => i,
+            None => match self.slots.iter().position(|slot| slot.code.is_empty()) {
+                Some(i) => i,
+                None => return false,
+            },
+        };
+
+        let slot = &mut self.slots[slot];
+        slot.code = code.into();
+        slot.quantity += quantity;
+
+        true
+    }
+
+    /// Remove items if there are enough of them
+    pub fn remove(&mut self, code: &str, quantity: i64) -> bool {
+        if self.count(code) < quantity {
+            return false;
+        }
+
+        let mut remaining = quantity;
+        for slot in self.slots.iter_mut().filter(|slot| slot.code == code) {
+            let taken = remaining.min(slot.quantity);
+            slot.quantity -= taken;
+            remaining -= taken;
+
+            if slot.quantity == 0 {
+                slot.code.clear();
+            }
+        }
+
+        true
+    }
+}
+
+impl<'de> Deserialize<'de> for Inventory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields = HashMap::<String, Value>::deserialize(deserializer)?;
+
+        let max_items = fields
+            .get("inventory_max_items")
+            .and_then(Value::as_i64)
+            .unwrap_or_default();
+
+        let slots = match fields.remove("inventory") {
+            Some(slots) => Vec::deserialize(slots).map_err(D::Error::custom)?,
+            None => (1..)
+                .map_while(|slot| {
+                    let code = fields.get(&format!("inventory_slot{slot}"))?;
+                    let quantity = fields.get(&format!("inventory_slot{slot}_quantity"));
+
+                    Some(InventorySlot {
+                        slot,
+                        code: code.as_str().unwrap_or_default().into(),
+                        quantity: quantity.and_then(Value::as_i64).unwrap_or_default(),
+                    })
+                })
+                .collect(),
+        };
+
+        Ok(Self { slots, max_items })
+    }
+}
+
+impl Serialize for Inventory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        map.serialize_entry("inventory_max_items", &self.max_items)?;
+        map.serialize_entry("inventory", &self.slots)?;
+        map.end()
+    }
+}
@@ -0,0 +1,28 @@
+pub mod action_queue;
+pub mod bank;
+pub mod changes;
+pub mod character;
+pub mod client;
+pub mod clock;
+pub mod combat;
+pub mod conditions;
+pub mod config;
+pub mod equipment;
+pub mod error;
+pub mod game_data;
+pub mod ge;
+pub mod history;
+pub mod inventory;
+pub mod map;
+pub mod metrics;
+pub mod mock;
+pub mod recipe;
+pub mod request;
+pub mod response;
+pub mod simulator;
+pub mod state;
+pub mod supervisor;
+pub mod tasks;
+pub mod validate;
+
+pub use character::Character;
@@ -1,94 +1,382 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
-use character::Character;
-use config::{CharacterConfig, Condition, Config, TaskName};
+use artifacts::{
+    client::{Client, DEFAULT_API_URL},
+    config::{CharacterConfig, Config, ConfigWatcher, DepositPolicy},
+    game_data::GameData,
+    history::History,
+    metrics::{self, Metrics},
+    simulator,
+    supervisor::Supervisor,
+    validate::{self, Severity},
+    Character,
+};
+use clap::{Parser, Subcommand};
 
-mod action_queue;
-mod character;
-mod conditions;
-mod config;
-mod error;
-mod response;
-mod tasks;
+mod commands;
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+const CONFIG_PATH: &str = "config.toml";
+
+// Simulated hours when `simulate` isn't given a duration
+const DEFAULT_SIMULATION_HOURS: f64 = 24.0;
+
+// How often the config file is checked for changes
+const RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+// How often the health of the characters is reported
+const HEALTH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Parser)]
+#[command(about = "Runs ArtifactsMMO characters from config.toml")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the characters in the config forever, the default
+    Run,
+    /// Run the config against the mock world in virtual time
+    #[command(allow_negative_numbers = true)]
+    Simulate {
+        #[arg(default_value_t = DEFAULT_SIMULATION_HOURS, value_parser = parse_hours)]
+        hours: f64,
+    },
+    /// Check the config against the game data
+    Validate,
+    /// Print a character's state
+    Status { character: String },
+    /// Move a character to a tile
+    #[command(allow_negative_numbers = true)]
+    Move { character: String, x: i32, y: i32 },
+    /// Fight the monster on the character's tile
+    Fight { character: String },
+    /// Gather the resource on the character's tile
+    Gather { character: String },
+    /// Craft an item at the workshop on the character's tile
+    Craft {
+        character: String,
+        code: String,
+        #[arg(default_value_t = 1)]
+        quantity: i64,
+    },
+    /// Walk to the nearest bank and deposit everything in the inventory
+    DepositAll { character: String },
+    /// Inspect the account's bank
+    Bank {
+        #[command(subcommand)]
+        command: BankCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum BankCommand {
+    /// Print the gold and items in the bank
+    List,
+}
+
+/// A simulation duration in hours, which must be positive and fit in a `Duration`
+fn parse_hours(hours: &str) -> Result<f64, String> {
+    let hours = hours.parse::<f64>().map_err(|e| e.to_string())?;
+
+    match Duration::try_from_secs_f64(hours * 3600.0) {
+        Ok(duration) if !duration.is_zero() => Ok(hours),
+        _ => Err("must be a positive number of hours".into()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
 
     dotenv::dotenv().ok();
-    let token = std::env::var("ARTIFACTS_TOKEN")?;
-
-    let client = reqwest::Client::new();
-
-    let config_str = tokio::fs::read_to_string("config.toml").await?;
-    let config: Config = toml::from_str(&config_str)?;
-
-    for character_config in config.characters.into_iter() {
-        let CharacterConfig { name, tasks } = character_config;
-
-        let character = Arc::new(Character::new(client.clone(), name, token.clone()));
-
-        let character_clone = character.clone();
-
-        tokio::spawn(async move {
-            let character = character_clone;
-
-            loop {
-                for task_config in &tasks {
-                    if let Some(condition) = &task_config.condition {
-                        match condition {
-                            Condition::FullInventory => {
-                                let is_full_inventory = match conditions::full_inventory(
-                                    &character,
-                                    task_config.name.clone(),
-                                )
-                                .await
-                                {
-                                    Ok(full) => full,
-                                    Err(e) => {
-                                        tracing::error!(error = ?e, "full inventory condition failed");
-                                        continue;
-                                    }
-                                };
-
-                                // skip the task if the inventory is not full
-                                if !is_full_inventory {
-                                    continue;
-                                }
-                            }
-                        }
-                    }
-
-                    match task_config.name {
-                        TaskName::MineCopper => tasks::mine_copper(&character).await.unwrap(),
-                        TaskName::MineIron => tasks::mine_iron(&character).await.unwrap(),
-                        TaskName::CopperIngots => tasks::copper_ingots(&character).await.unwrap(),
-                        TaskName::KillChickens => tasks::kill_chickens(&character).await.unwrap(),
-                        TaskName::DepositInventory => {
-                            if let Err(e) = tasks::deposit_inventory(&character).await {
-                                tracing::error!(error = ?e, "depositing inventory failed");
-                                continue;
-                            }
-                        }
-                        TaskName::KillYellowSlime => {
-                            tasks::kill_yellow_slime(&character).await.unwrap()
-                        }
-                        TaskName::KillCows => tasks::kill_cows(&character).await.unwrap(),
-                    }
+
+    let command = Cli::parse().command.unwrap_or(Command::Run);
+    if let Command::Validate = command {
+        return tokio::runtime::Runtime::new()?.block_on(check());
+    }
+
+    let config = Config::load(CONFIG_PATH)?;
+
+    match command {
+        Command::Simulate { hours } => {
+            let report = simulator::simulate(&config, Duration::from_secs_f64(hours * 3600.0), 0)?;
+            println!("{report}");
+
+            Ok(())
+        }
+        Command::Run => tokio::runtime::Runtime::new()?.block_on(run(config)),
+        command => tokio::runtime::Runtime::new()?.block_on(manual(config, command)),
+    }
+}
+
+/// Run a one-off command
+async fn manual(config: Config, command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    let launcher = Launcher::new(&config)?;
+
+    let name = match &command {
+        Command::Bank {
+            command: BankCommand::List,
+        } => return commands::bank_list(&launcher.account_client(&config)?).await,
+        Command::Status { character }
+        | Command::Move { character, .. }
+        | Command::Fight { character }
+        | Command::Gather { character }
+        | Command::Craft { character, .. }
+        | Command::DepositAll { character } => character,
+        Command::Run | Command::Simulate { .. } | Command::Validate => {
+            unreachable!("handled by main")
+        }
+    };
+
+    let mut character = launcher.load(&config, name).await?;
+    if let Command::DepositAll { .. } = command {
+        // everything, whatever the configured policy keeps
+        character = character.with_deposit_policy(DepositPolicy::default());
+    }
+
+    let character = Arc::new(character);
+    let executor = character.clone();
+    tokio::spawn(async move { executor.execution_loop().await });
+
+    let result = match command {
+        Command::Status { .. } => commands::status(&character),
+        Command::Move { x, y, .. } => commands::move_to(&character, x, y).await,
+        Command::Fight { .. } => commands::fight(&character).await,
+        Command::Gather { .. } => commands::gather(&character).await,
+        Command::Craft { code, quantity, .. } => commands::craft(&character, &code, quantity).await,
+        Command::DepositAll { .. } => commands::deposit_all(&character).await,
+        _ => unreachable!("handled above"),
+    };
+
+    // the process exits right after, so don't lose the last actions
+    if let Some(history) = &launcher.history {
+        history.flush().await?;
+    }
+
+    result
+}
+
+/// Print every problem with the config, failing if any is an error
+async fn check() -> Result<(), Box<dyn std::error::Error>> {
+    let source = std::fs::read_to_string(CONFIG_PATH)?;
+
+    let api_url = toml::from_str::<Config>(&source)
+        .map(|config| config.api_url)
+        .unwrap_or_else(|_| DEFAULT_API_URL.into());
+    let game = GameData::new(Client::new(reqwest::Client::new(), &api_url, String::new()));
+
+    let diagnostics = validate::validate(&source, &game).await?;
+    for diagnostic in &diagnostics {
+        println!("{CONFIG_PATH}:{diagnostic}");
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(format!("{errors} errors in {CONFIG_PATH}").into());
+    }
+
+    println!("{CONFIG_PATH} is valid");
+
+    Ok(())
+}
+
+async fn run(config: Config) -> Result<(), Box<dyn std::error::Error>> {
+    let mut launcher = Launcher::new(&config)?;
+
+    if let Some(metrics_config) = &config.metrics {
+        let listener = tokio::net::TcpListener::bind(&metrics_config.listen).await?;
+        tracing::info!(
+            "serving metrics on http://{}/metrics",
+            metrics_config.listen
+        );
+
+        let metrics = Metrics::new();
+        tokio::spawn(metrics::serve(metrics.clone(), listener));
+        launcher.metrics = Some(metrics);
+    }
+
+    for character_config in config.characters.iter() {
+        launcher.start(&config, character_config).await?;
+    }
+
+    let mut watcher = ConfigWatcher::new(CONFIG_PATH);
+    let mut reload = tokio::time::interval(RELOAD_INTERVAL);
+    let mut health = tokio::time::interval(HEALTH_INTERVAL);
+
+    // reload the config and report the health of the characters forever
+    loop {
+        tokio::select! {
+            _ = reload.tick() => match watcher.poll() {
+                Some(Ok((config, source))) => match launcher.check(&source).await {
+                    Ok(()) => launcher.reload(config).await,
+                    Err(e) => tracing::error!(error = %e, "invalid config, not reloading"),
+                },
+                Some(Err(e)) => tracing::error!(error = %e, "invalid config, not reloading"),
+                None => {}
+            },
+            _ = health.tick() => {
+                let supervisor = &launcher.supervisor;
+                if !supervisor.is_healthy() {
+                    tracing::warn!(health = ?supervisor.health(), "characters unhealthy");
                 }
             }
-        });
+        }
+    }
+}
 
-        tokio::spawn(async move {
-            character
-                .execution_loop()
-                .await
-                .expect("execution loop failed");
-        });
+/// Loads characters from the config and starts them under the supervisor, at startup and on
+/// reload
+struct Launcher {
+    http: reqwest::Client,
+    api_url: String,
+    default_token: Option<String>,
+    game: Arc<GameData>,
+    supervisor: Supervisor,
+    metrics: Option<Metrics>,
+    history: Option<History>,
+}
+
+impl Launcher {
+    fn new(config: &Config) -> std::io::Result<Self> {
+        let http = reqwest::Client::new();
+
+        let history = match &config.history {
+            Some(history_config) => Some(History::open(history_config)?),
+            None => None,
+        };
+
+        Ok(Self {
+            game: Arc::new(GameData::new(Client::new(
+                http.clone(),
+                &config.api_url,
+                String::new(),
+            ))),
+            http,
+            api_url: config.api_url.clone(),
+            default_token: std::env::var("ARTIFACTS_TOKEN").ok(),
+            supervisor: Supervisor::new(config.retry.clone()),
+            metrics: None,
+            history,
+        })
     }
 
-    // sleep forever
-    loop {
-        tokio::time::sleep(Duration::from_secs(60)).await;
+    fn client(&self, name: &str, token: Option<&String>) -> Result<Client, String> {
+        match token.or(self.default_token.as_ref()) {
+            Some(token) => Ok(Client::new(self.http.clone(), &self.api_url, token.clone())),
+            None => Err(format!("no token configured for character {name}")),
+        }
+    }
+
+    /// A client for the account, authenticated as `ARTIFACTS_TOKEN` or the first character
+    fn account_client(&self, config: &Config) -> Result<Client, String> {
+        let token = self
+            .default_token
+            .as_ref()
+            .or_else(|| config.characters.first()?.token.as_ref());
+
+        match token {
+            Some(token) => Ok(Client::new(self.http.clone(), &self.api_url, token.clone())),
+            None => Err("no token configured for the account".into()),
+        }
+    }
+
+    /// Load a character with its policies from the config, or the defaults if it isn't
+    /// configured
+    async fn load(
+        &self,
+        config: &Config,
+        name: &str,
+    ) -> Result<Character, Box<dyn std::error::Error>> {
+        let character_config = config.characters.iter().find(|c| c.name == name);
+        let token = character_config.and_then(|c| c.token.as_ref());
+        let deposit = character_config.and_then(|c| c.deposit.clone());
+
+        let client = self.client(name, token)?;
+        let mut character = Character::load(client, name.to_string(), self.game.clone())
+            .await?
+            .with_heal_policy(config.heal.clone())
+            .with_deposit_policy(deposit.unwrap_or_else(|| config.deposit.clone()));
+
+        if let Some(metrics) = &self.metrics {
+            character = character.with_metrics(metrics.clone());
+        }
+        if let Some(history) = &self.history {
+            character = character.with_history(history.clone());
+        }
+
+        Ok(character)
+    }
+
+    async fn start(
+        &self,
+        config: &Config,
+        character_config: &CharacterConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let character = self.load(config, &character_config.name).await?;
+
+        self.supervisor
+            .supervise(Arc::new(character), character_config.tasks.clone());
+
+        Ok(())
+    }
+
+    /// Check a reloaded config against the game data like `validate` does, logging what it
+    /// reports. Fails if there are any errors, so the running config is kept.
+    async fn check(&self, source: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let diagnostics = validate::validate(source, &self.game).await?;
+
+        let mut errors = 0;
+        for diagnostic in &diagnostics {
+            match diagnostic.severity {
+                Severity::Error => {
+                    tracing::error!("{CONFIG_PATH}:{diagnostic}");
+                    errors += 1;
+                }
+                Severity::Warning => tracing::warn!("{CONFIG_PATH}:{diagnostic}"),
+            }
+        }
+
+        if errors > 0 {
+            return Err(format!("{errors} errors in {CONFIG_PATH}").into());
+        }
+
+        Ok(())
+    }
+
+    /// Swap the tasks of running characters, start new characters and stop removed ones.
+    ///
+    /// Running characters keep their token and policies, those only apply to characters
+    /// started by the reload.
+    async fn reload(&self, config: Config) {
+        if config.api_url != self.api_url {
+            tracing::warn!("api_url can't be changed without restarting");
+        }
+
+        let configured: HashSet<&str> = config.characters.iter().map(|c| c.name.as_str()).collect();
+        for name in self.supervisor.characters() {
+            if !configured.contains(name.as_str()) {
+                tracing::info!(character = %name, "stopping character removed from config");
+                self.supervisor.stop(&name);
+            }
+        }
+
+        for character_config in &config.characters {
+            let name = &character_config.name;
+
+            if self
+                .supervisor
+                .set_tasks(name, character_config.tasks.clone())
+            {
+                tracing::info!(character = %name, "reloaded tasks");
+            } else if let Err(e) = self.start(&config, character_config).await {
+                tracing::error!(character = %name, error = %e, "failed to start character");
+            } else {
+                tracing::info!(character = %name, "started character added to config");
+            }
+        }
     }
 }
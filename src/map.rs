@@ -0,0 +1,69 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{sync::RwLock, time::Instant};
+
+use crate::{client::Client, error::Error, response::Map};
+
+// How long the map is cached before it is fetched again, events change map content
+const MAP_TTL: Duration = Duration::from_secs(300);
+
+/// Cached world map, fetched from the API when first used and again once it is stale
+pub struct WorldMap {
+    client: Client,
+    cache: RwLock<Option<(Instant, Arc<Vec<Map>>)>>,
+}
+
+impl WorldMap {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            cache: RwLock::new(None),
+        }
+    }
+
+    /// Every tile of the map
+    pub async fn tiles(&self) -> Result<Arc<Vec<Map>>, Error> {
+        if let Some((fetched, tiles)) = &*self.cache.read().await {
+            if fetched.elapsed() < MAP_TTL {
+                return Ok(tiles.clone());
+            }
+        }
+
+        let mut cache = self.cache.write().await;
+
+        // another task may have fetched the map while we were waiting for the lock
+        if let Some((fetched, tiles)) = &*cache {
+            if fetched.elapsed() < MAP_TTL {
+                return Ok(tiles.clone());
+            }
+        }
+
+        let tiles = Arc::new(self.client.all_maps().await?);
+        tracing::debug!("Fetched {} map tiles", tiles.len());
+
+        *cache = Some((Instant::now(), tiles.clone()));
+
+        Ok(tiles)
+    }
+
+    /// Drop the cached map so it is fetched again, e.g. when content was not where expected
+    pub async fn invalidate(&self) {
+        *self.cache.write().await = None;
+    }
+
+    /// The tile with content `code` closest to `from`, e.g. `iron_rocks`, `chicken`, `mining`
+    /// or `bank`
+    pub async fn nearest(&self, code: &str, from: (i64, i64)) -> Result<Option<Map>, Error> {
+        let tiles = self.tiles().await?;
+
+        Ok(tiles
+            .iter()
+            .filter(|tile| {
+                tile.content
+                    .as_ref()
+                    .is_some_and(|content| content.code == code)
+            })
+            .min_by_key(|tile| (tile.x - from.0).abs() + (tile.y - from.1).abs())
+            .cloned())
+    }
+}
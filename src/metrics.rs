@@ -0,0 +1,223 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::{
+    changes::Changes,
+    request::Action,
+    response::{ActionData, Character, FightResult},
+};
+
+// (labels, value) of each sample of a metric
+type Samples = Vec<(String, String)>;
+
+#[derive(Default)]
+struct CharacterMetrics {
+    actions: BTreeMap<&'static str, u64>,
+    errors: BTreeMap<(&'static str, i32), u64>,
+    xp: BTreeMap<String, i64>,
+    items_gathered: BTreeMap<String, i64>,
+    gold: i64,
+    deaths: u64,
+    idle: Duration,
+    queue_depth: usize,
+}
+
+/// Counters and gauges of every character, populated by their action queues and rendered in
+/// the Prometheus text format
+#[derive(Clone, Default)]
+pub struct Metrics(Arc<Mutex<BTreeMap<String, CharacterMetrics>>>);
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an action that succeeded, `before` being the character state it started from
+    pub fn action(&self, name: &str, action: &Action, before: &Character, data: &ActionData) {
+        let changes = Changes::between(before, &data.character);
+
+        self.update(name, |metrics| {
+            *metrics.actions.entry(action.path()).or_default() += 1;
+
+            for (skill, xp) in changes.xp {
+                *metrics.xp.entry(skill).or_default() += xp;
+            }
+
+            if let (Action::Gathering, Some(details)) = (action, &data.details) {
+                for item in &details.items {
+                    *metrics.items_gathered.entry(item.code.clone()).or_default() += item.quantity;
+                }
+            }
+
+            if data
+                .fight
+                .as_ref()
+                .is_some_and(|fight| fight.result == FightResult::Lose)
+            {
+                metrics.deaths += 1;
+            }
+
+            metrics.gold = data.character.gold;
+        });
+    }
+
+    /// Record an action the API rejected with an error `code`
+    pub fn error(&self, name: &str, action: &Action, code: i32) {
+        self.update(name, |metrics| {
+            *metrics.errors.entry((action.path(), code)).or_default() += 1;
+        });
+    }
+
+    /// Record time the character's cooldown was over with no action to execute
+    pub fn idle(&self, name: &str, idle: Duration) {
+        self.update(name, |metrics| metrics.idle += idle);
+    }
+
+    /// Set the number of actions waiting to be executed
+    pub fn queue_depth(&self, name: &str, depth: usize) {
+        self.update(name, |metrics| metrics.queue_depth = depth);
+    }
+
+    /// Every metric in the Prometheus text format
+    pub fn render(&self) -> String {
+        let characters = self.0.lock().expect("metrics lock poisoned");
+        let mut out = String::new();
+
+        let mut family = |name: &str, kind: &str, help: &str, samples: Samples| {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{name}{{{labels}}} {value}");
+            }
+        };
+
+        let samples = |f: &dyn Fn(&str, &CharacterMetrics) -> Samples| {
+            characters
+                .iter()
+                .flat_map(|(name, metrics)| f(&format!("character=\"{name}\""), metrics))
+                .collect::<Samples>()
+        };
+
+        family(
+            "artifacts_actions_total",
+            "counter",
+            "Actions that succeeded, by type",
+            samples(&|character, m| {
+                m.actions
+                    .iter()
+                    .map(|(action, n)| (format!("{character},action=\"{action}\""), n.to_string()))
+                    .collect()
+            }),
+        );
+        family(
+            "artifacts_api_errors_total",
+            "counter",
+            "Actions rejected by the API, by type and error code",
+            samples(&|character, m| {
+                m.errors
+                    .iter()
+                    .map(|((action, code), n)| {
+                        (
+                            format!("{character},action=\"{action}\",code=\"{code}\""),
+                            n.to_string(),
+                        )
+                    })
+                    .collect()
+            }),
+        );
+        family(
+            "artifacts_xp_gained_total",
+            "counter",
+            "XP gained, by skill",
+            samples(&|character, m| {
+                m.xp.iter()
+                    .map(|(skill, xp)| (format!("{character},skill=\"{skill}\""), xp.to_string()))
+                    .collect()
+            }),
+        );
+        family(
+            "artifacts_items_gathered_total",
+            "counter",
+            "Items gathered, by item",
+            samples(&|character, m| {
+                m.items_gathered
+                    .iter()
+                    .map(|(item, n)| (format!("{character},item=\"{item}\""), n.to_string()))
+                    .collect()
+            }),
+        );
+        family(
+            "artifacts_gold",
+            "gauge",
+            "Gold held by the character",
+            samples(&|character, m| vec![(character.to_string(), m.gold.to_string())]),
+        );
+        family(
+            "artifacts_deaths_total",
+            "counter",
+            "Fights lost",
+            samples(&|character, m| vec![(character.to_string(), m.deaths.to_string())]),
+        );
+        family(
+            "artifacts_cooldown_idle_seconds_total",
+            "counter",
+            "Time spent off cooldown with no action to execute",
+            samples(&|character, m| {
+                vec![(character.to_string(), m.idle.as_secs_f64().to_string())]
+            }),
+        );
+        family(
+            "artifacts_queue_depth",
+            "gauge",
+            "Actions waiting to be executed",
+            samples(&|character, m| vec![(character.to_string(), m.queue_depth.to_string())]),
+        );
+
+        out
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut CharacterMetrics)) {
+        let mut characters = self.0.lock().expect("metrics lock poisoned");
+
+        f(characters.entry(name.to_string()).or_default());
+    }
+}
+
+/// Serve the metrics at `/metrics` over HTTP forever
+pub async fn serve(metrics: Metrics, listener: TcpListener) -> std::io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            // the request line is all we need, and it fits in the first read
+            let mut request = [0; 1024];
+            let n = stream.read(&mut request).await?;
+            let request = String::from_utf8_lossy(&request[..n]);
+
+            let response = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+                ["GET", "/metrics"] => {
+                    let body = metrics.render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                }
+                _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string(),
+            };
+
+            stream.write_all(response.as_bytes()).await?;
+            stream.shutdown().await
+        });
+    }
+}
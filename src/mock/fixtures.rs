@@ -0,0 +1,289 @@
+use crate::response::{
+    Craft, DropRate, GEItem, Item, ItemEffect, Map, MapContent, Monster, Resource, SimpleItem,
+};
+
+fn tile(x: i64, y: i64, content: Option<(&str, &str)>) -> Map {
+    Map {
+        name: content.map(|(_, code)| code).unwrap_or("grass").into(),
+        skin: "forest_1".into(),
+        x,
+        y,
+        content: content.map(|(kind, code)| MapContent {
+            kind: kind.into(),
+            code: code.into(),
+        }),
+    }
+}
+
+/// Every tile from (-5, -5) to (5, 10), with content placed where it is in the real world
+pub fn maps() -> Vec<Map> {
+    let content = [
+        ((0, 1), ("monster", "chicken")),
+        ((0, 2), ("monster", "cow")),
+        ((4, -1), ("monster", "yellow_slime")),
+        ((2, 0), ("resource", "copper_rocks")),
+        ((1, 7), ("resource", "iron_rocks")),
+        ((-1, 0), ("resource", "ash_tree")),
+        ((4, 1), ("bank", "bank")),
+        ((5, 1), ("grand_exchange", "grand_exchange")),
+        ((1, 5), ("workshop", "mining")),
+        ((2, 1), ("workshop", "weaponcrafting")),
+        ((3, 1), ("workshop", "gearcrafting")),
+        ((1, 1), ("workshop", "cooking")),
+        ((-2, 0), ("workshop", "woodcutting")),
+        ((1, 2), ("tasks_master", "monsters")),
+        ((3, 2), ("tasks_master", "items")),
+    ];
+
+    let mut maps = vec![];
+    for y in -5..=10 {
+        for x in -5..=5 {
+            let found = content
+                .iter()
+                .find(|(position, _)| *position == (x, y))
+                .map(|(_, content)| *content);
+
+            maps.push(tile(x, y, found));
+        }
+    }
+
+    maps
+}
+
+fn item(code: &str, level: i64, kind: &str, subtype: &str) -> Item {
+    Item {
+        name: code.replace('_', " "),
+        code: code.into(),
+        level,
+        kind: kind.into(),
+        subtype: subtype.into(),
+        description: String::new(),
+        effects: vec![],
+        craft: None,
+    }
+}
+
+fn effects(item: Item, effects: &[(&str, i64)]) -> Item {
+    Item {
+        effects: effects
+            .iter()
+            .map(|(name, value)| ItemEffect {
+                name: (*name).into(),
+                value: *value,
+            })
+            .collect(),
+        ..item
+    }
+}
+
+fn craft(item: Item, skill: &str, level: i64, items: &[(&str, i64)], quantity: i64) -> Item {
+    Item {
+        craft: Some(Craft {
+            skill: skill.into(),
+            level,
+            items: items
+                .iter()
+                .map(|(code, quantity)| SimpleItem {
+                    code: (*code).into(),
+                    quantity: *quantity,
+                })
+                .collect(),
+            quantity,
+        }),
+        ..item
+    }
+}
+
+pub fn items() -> Vec<Item> {
+    vec![
+        item("copper_ore", 1, "resource", "mining"),
+        item("iron_ore", 10, "resource", "mining"),
+        item("ash_wood", 1, "resource", "woodcutting"),
+        item("feather", 1, "resource", "mob"),
+        item("raw_chicken", 1, "resource", "mob"),
+        item("egg", 1, "resource", "mob"),
+        item("raw_beef", 1, "resource", "mob"),
+        item("yellow_slimeball", 1, "resource", "mob"),
+        item("tasks_coin", 1, "resource", "task"),
+        craft(
+            item("copper", 1, "resource", "bar"),
+            "mining",
+            1,
+            &[("copper_ore", 10)],
+            1,
+        ),
+        craft(
+            item("iron", 10, "resource", "bar"),
+            "mining",
+            10,
+            &[("iron_ore", 10)],
+            1,
+        ),
+        craft(
+            item("ash_plank", 1, "resource", "plank"),
+            "woodcutting",
+            1,
+            &[("ash_wood", 10)],
+            1,
+        ),
+        craft(
+            effects(
+                item("cooked_chicken", 1, "consumable", "food"),
+                &[("heal", 75)],
+            ),
+            "cooking",
+            1,
+            &[("raw_chicken", 1)],
+            1,
+        ),
+        effects(
+            item("wooden_stick", 1, "weapon", ""),
+            &[("attack_earth", 4)],
+        ),
+        craft(
+            effects(
+                item("copper_dagger", 1, "weapon", ""),
+                &[("attack_air", 6), ("critical_strike", 35)],
+            ),
+            "weaponcrafting",
+            1,
+            &[("copper", 6)],
+            1,
+        ),
+        craft(
+            effects(
+                item("iron_pickaxe", 10, "weapon", "tool"),
+                &[("attack_earth", 5), ("mining", -10)],
+            ),
+            "weaponcrafting",
+            10,
+            &[("iron", 6)],
+            1,
+        ),
+        craft(
+            effects(
+                item("wooden_shield", 1, "shield", ""),
+                &[
+                    ("res_fire", 4),
+                    ("res_earth", 4),
+                    ("res_water", 4),
+                    ("res_air", 4),
+                ],
+            ),
+            "gearcrafting",
+            1,
+            &[("ash_plank", 6)],
+            1,
+        ),
+        craft(
+            effects(
+                item("copper_helmet", 1, "helmet", ""),
+                &[("hp", 10), ("res_water", 5)],
+            ),
+            "gearcrafting",
+            1,
+            &[("copper", 6)],
+            1,
+        ),
+    ]
+}
+
+fn drop(code: &str, rate: i64) -> DropRate {
+    DropRate {
+        code: code.into(),
+        rate,
+        min_quantity: 1,
+        max_quantity: 1,
+    }
+}
+
+pub fn resources() -> Vec<Resource> {
+    vec![
+        Resource {
+            name: "Copper Rocks".into(),
+            code: "copper_rocks".into(),
+            skill: "mining".into(),
+            level: 1,
+            drops: vec![drop("copper_ore", 1)],
+        },
+        Resource {
+            name: "Iron Rocks".into(),
+            code: "iron_rocks".into(),
+            skill: "mining".into(),
+            level: 10,
+            drops: vec![drop("iron_ore", 1)],
+        },
+        Resource {
+            name: "Ash Tree".into(),
+            code: "ash_tree".into(),
+            skill: "woodcutting".into(),
+            level: 1,
+            drops: vec![drop("ash_wood", 1)],
+        },
+    ]
+}
+
+pub fn monsters() -> Vec<Monster> {
+    vec![
+        Monster {
+            name: "Chicken".into(),
+            code: "chicken".into(),
+            level: 1,
+            hp: 60,
+            attack_water: 4,
+            min_gold: 0,
+            max_gold: 3,
+            drops: vec![drop("raw_chicken", 10), drop("egg", 12), drop("feather", 8)],
+            ..Default::default()
+        },
+        Monster {
+            name: "Yellow Slime".into(),
+            code: "yellow_slime".into(),
+            level: 2,
+            hp: 70,
+            attack_earth: 8,
+            res_earth: 25,
+            min_gold: 0,
+            max_gold: 4,
+            drops: vec![drop("yellow_slimeball", 4)],
+            ..Default::default()
+        },
+        Monster {
+            name: "Cow".into(),
+            code: "cow".into(),
+            level: 8,
+            hp: 180,
+            attack_earth: 10,
+            res_air: 10,
+            min_gold: 0,
+            max_gold: 8,
+            drops: vec![drop("raw_beef", 10)],
+            ..Default::default()
+        },
+    ]
+}
+
+/// What exchanging tasks coins can give, with equal chances
+pub fn task_rewards() -> Vec<(&'static str, i64)> {
+    vec![("cooked_chicken", 5), ("copper", 2), ("feather", 10)]
+}
+
+/// Grand Exchange listings of the raw materials and the food
+pub fn ge() -> Vec<GEItem> {
+    [
+        ("copper_ore", 1000, 4, 6),
+        ("iron_ore", 500, 10, 14),
+        ("ash_wood", 1000, 3, 5),
+        ("copper", 200, 45, 60),
+        ("feather", 300, 2, 3),
+        ("cooked_chicken", 400, 15, 22),
+    ]
+    .into_iter()
+    .map(|(code, stock, sell_price, buy_price)| GEItem {
+        code: code.into(),
+        stock,
+        sell_price: Some(sell_price),
+        buy_price: Some(buy_price),
+    })
+    .collect()
+}
@@ -0,0 +1,1236 @@
+//! An in-process model of the ArtifactsMMO game, answering API requests the way the real
+//! server does: characters with cooldowns, a map with resources, monsters, workshops and a
+//! bank, and the error codes actions fail with. Used to run the bot without the live API.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    combat,
+    equipment::{apply_effects, Slot},
+    ge::MAX_TRANSACTION_QUANTITY,
+    inventory::Inventory,
+    response::{Character, GEItem, Item, Map, Monster, Resource, SimpleItem},
+};
+
+pub mod fixtures;
+
+/// Cooldown of each action in seconds
+#[derive(Debug, Clone)]
+pub struct Cooldowns {
+    pub move_per_tile: f64,
+    pub gathering: f64,
+    pub fight: f64,
+    /// Per item crafted
+    pub crafting: f64,
+    pub bank: f64,
+    /// Also used for consumables
+    pub equip: f64,
+    /// Minimum rest
+    pub rest: f64,
+    pub rest_per_hp: f64,
+    /// Accepting, completing, cancelling and exchanging tasks
+    pub task: f64,
+}
+
+impl Default for Cooldowns {
+    fn default() -> Self {
+        Self {
+            move_per_tile: 5.0,
+            gathering: 25.0,
+            fight: 20.0,
+            crafting: 5.0,
+            bank: 3.0,
+            equip: 3.0,
+            rest: 3.0,
+            rest_per_hp: 0.2,
+            task: 3.0,
+        }
+    }
+}
+
+impl Cooldowns {
+    /// Every action takes `seconds`, for tests that don't care about cooldowns
+    pub fn uniform(seconds: f64) -> Self {
+        Self {
+            move_per_tile: seconds,
+            gathering: seconds,
+            fight: seconds,
+            crafting: seconds,
+            bank: seconds,
+            equip: seconds,
+            rest: seconds,
+            rest_per_hp: 0.0,
+            task: seconds,
+        }
+    }
+}
+
+// Where characters start and respawn after losing a fight
+const SPAWN: (i64, i64) = (0, 0);
+const STARTING_HP: i64 = 120;
+// Tasks coins a task exchange costs
+const TASK_EXCHANGE_COST: i64 = 3;
+
+/// An API error, the HTTP status and error code are the same
+type Failure = (u16, String);
+
+fn fail<T>(code: u16, message: impl Into<String>) -> Result<T, Failure> {
+    Err((code, message.into()))
+}
+
+/// The result of an action: its cooldown and any extra response fields
+struct Outcome {
+    seconds: f64,
+    reason: &'static str,
+    extra: Value,
+}
+
+pub struct World {
+    token: Option<String>,
+    cooldowns: Cooldowns,
+    characters: BTreeMap<String, Character>,
+    maps: Vec<Map>,
+    items: Vec<Item>,
+    resources: Vec<Resource>,
+    monsters: Vec<Monster>,
+    ge: Vec<GEItem>,
+    bank: Vec<SimpleItem>,
+    bank_gold: i64,
+    bank_expansions: i64,
+    rng: StdRng,
+}
+
+impl World {
+    /// The default world with realistic cooldowns, random drops are seeded with `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            token: None,
+            cooldowns: Cooldowns::default(),
+            characters: BTreeMap::new(),
+            maps: fixtures::maps(),
+            items: fixtures::items(),
+            resources: fixtures::resources(),
+            monsters: fixtures::monsters(),
+            ge: fixtures::ge(),
+            bank: vec![],
+            bank_gold: 0,
+            bank_expansions: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Require `my/...` requests to send this token in the `Authorization` header
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn with_cooldowns(mut self, cooldowns: Cooldowns) -> Self {
+        self.cooldowns = cooldowns;
+        self
+    }
+
+    /// Create a level 1 character at the spawn with a wooden stick equipped
+    pub fn add_character(&mut self, name: &str) -> &mut Character {
+        let character = Character {
+            name: name.into(),
+            skin: "men1".into(),
+            level: 1,
+            max_xp: 150,
+            mining_level: 1,
+            mining_max_xp: 150,
+            woodcutting_level: 1,
+            woodcutting_max_xp: 150,
+            fishing_level: 1,
+            fishing_max_xp: 150,
+            weaponcrafting_level: 1,
+            weaponcrafting_max_xp: 150,
+            gearcrafting_level: 1,
+            gearcrafting_max_xp: 150,
+            jewelrycrafting_level: 1,
+            jewelrycrafting_max_xp: 150,
+            cooking_level: 1,
+            cooking_max_xp: 150,
+            hp: STARTING_HP,
+            max_hp: STARTING_HP,
+            speed: 100,
+            attack_earth: 4,
+            x: SPAWN.0,
+            y: SPAWN.1,
+            weapon_slot: "wooden_stick".into(),
+            inventory: Inventory::new(20, 100),
+            ..Default::default()
+        };
+
+        self.characters.insert(name.into(), character);
+        self.characters
+            .get_mut(name)
+            .expect("character was just added")
+    }
+
+    pub fn characters(&self) -> impl Iterator<Item = &Character> {
+        self.characters.values()
+    }
+
+    pub fn character(&self, name: &str) -> Option<&Character> {
+        self.characters.get(name)
+    }
+
+    pub fn character_mut(&mut self, name: &str) -> Option<&mut Character> {
+        self.characters.get_mut(name)
+    }
+
+    /// Put items in a character's inventory, ignoring the inventory limit
+    pub fn give(&mut self, name: &str, code: &str, quantity: i64) {
+        if let Some(character) = self.characters.get_mut(name) {
+            character.inventory.insert(code, quantity);
+        }
+    }
+
+    pub fn bank(&self) -> &[SimpleItem] {
+        &self.bank
+    }
+
+    pub fn bank_gold(&self) -> i64 {
+        self.bank_gold
+    }
+
+    pub fn deposit_in_bank(&mut self, code: &str, quantity: i64) {
+        add_to_bank(&mut self.bank, code, quantity);
+    }
+
+    /// Answer an API request, returning the HTTP status and JSON body
+    pub fn handle(
+        &mut self,
+        method: &str,
+        path: &str,
+        query: &str,
+        token: Option<&str>,
+        body: &[u8],
+        now: DateTime<Utc>,
+    ) -> (u16, Value) {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let result = if segments.first() == Some(&"my") && !self.authorized(token) {
+            fail(452, "Token is missing or invalid.")
+        } else {
+            let body: Value = serde_json::from_slice(body).unwrap_or(Value::Null);
+
+            match (method, segments.as_slice()) {
+                ("GET", []) => Ok(json!({ "data": {
+                    "status": "online",
+                    "version": "mock",
+                    "characters_online": self.characters.len(),
+                    "server_time": now,
+                }})),
+                ("GET", ["characters"]) => Ok(page(self.characters.values(), query)),
+                ("GET", ["characters", name]) => self.get_character(name).map(data),
+                ("GET", ["my", "characters"]) => {
+                    Ok(data(self.characters.values().collect::<Vec<_>>()))
+                }
+                ("GET", ["my", "bank"]) => Ok(data(json!({
+                    "slots": self.bank_slots(),
+                    "expansions": self.bank_expansions,
+                    "next_expansion_cost": self.next_expansion_cost(),
+                    "gold": self.bank_gold,
+                }))),
+                ("GET", ["my", "bank", "items"]) => Ok(page(&self.bank, query)),
+                ("GET", ["maps"]) => Ok(page(&self.maps, query)),
+                ("GET", ["maps", x, y]) => self.get_map(x, y).map(data),
+                ("GET", ["items"]) => Ok(page(&self.items, query)),
+                ("GET", ["items", code]) => self.get_item(code).map(|item| {
+                    let ge = self.ge.iter().find(|listing| listing.code == item.code);
+
+                    data(json!({ "item": item, "ge": ge }))
+                }),
+                ("GET", ["monsters"]) => Ok(page(&self.monsters, query)),
+                ("GET", ["monsters", code]) => self.get_monster(code).map(data),
+                ("GET", ["ge"]) => Ok(page(&self.ge, query)),
+                ("GET", ["ge", code]) => self.get_listing(code).map(|listing| data(&*listing)),
+                ("GET", ["resources"]) => Ok(page(&self.resources, query)),
+                ("GET", ["resources", code]) => self.get_resource(code).map(data),
+                ("POST", ["my", name, "action", action @ ..]) => {
+                    self.action(name, &action.join("/"), &body, now)
+                }
+                _ => fail(404, "Not found."),
+            }
+        };
+
+        match result {
+            Ok(body) => (200, body),
+            Err((code, message)) => (
+                code,
+                json!({ "error": { "code": code, "message": message } }),
+            ),
+        }
+    }
+
+    fn bank_slots(&self) -> i64 {
+        50 + 20 * self.bank_expansions
+    }
+
+    fn next_expansion_cost(&self) -> i64 {
+        4500 * (self.bank_expansions + 1)
+    }
+
+    fn authorized(&self, token: Option<&str>) -> bool {
+        match &self.token {
+            Some(expected) => token == Some(expected.as_str()),
+            None => true,
+        }
+    }
+
+    fn get_character(&self, name: &str) -> Result<&Character, Failure> {
+        match self.characters.get(name) {
+            Some(character) => Ok(character),
+            None => fail(404, "Character not found."),
+        }
+    }
+
+    fn get_map(&self, x: &str, y: &str) -> Result<&Map, Failure> {
+        let (x, y) = match (x.parse::<i64>(), y.parse::<i64>()) {
+            (Ok(x), Ok(y)) => (x, y),
+            _ => return fail(404, "Map not found."),
+        };
+
+        match self.maps.iter().find(|map| map.x == x && map.y == y) {
+            Some(map) => Ok(map),
+            None => fail(404, "Map not found."),
+        }
+    }
+
+    fn get_item(&self, code: &str) -> Result<&Item, Failure> {
+        match self.items.iter().find(|item| item.code == code) {
+            Some(item) => Ok(item),
+            None => fail(404, "Item not found."),
+        }
+    }
+
+    fn get_monster(&self, code: &str) -> Result<&Monster, Failure> {
+        match self.monsters.iter().find(|monster| monster.code == code) {
+            Some(monster) => Ok(monster),
+            None => fail(404, "Monster not found."),
+        }
+    }
+
+    fn get_listing(&mut self, code: &str) -> Result<&mut GEItem, Failure> {
+        match self.ge.iter_mut().find(|listing| listing.code == code) {
+            Some(listing) => Ok(listing),
+            None => fail(404, "Item not found."),
+        }
+    }
+
+    fn get_resource(&self, code: &str) -> Result<&Resource, Failure> {
+        match self.resources.iter().find(|resource| resource.code == code) {
+            Some(resource) => Ok(resource),
+            None => fail(404, "Resource not found."),
+        }
+    }
+
+    /// Content of the tile the character is standing on
+    fn content_at(&self, character: &Character, kind: &str) -> Option<String> {
+        self.maps
+            .iter()
+            .find(|map| map.x == character.x && map.y == character.y)
+            .and_then(|map| map.content.as_ref())
+            .filter(|content| content.kind == kind)
+            .map(|content| content.code.clone())
+    }
+
+    fn action(
+        &mut self,
+        name: &str,
+        action: &str,
+        body: &Value,
+        now: DateTime<Utc>,
+    ) -> Result<Value, Failure> {
+        let character = match self.characters.get(name) {
+            Some(character) => character.clone(),
+            None => return fail(498, "Character not found."),
+        };
+
+        if let Some(expiration) = character.cooldown_expiration {
+            if expiration > now {
+                let remaining = (expiration - now).num_milliseconds() as f64 / 1000.0;
+                return fail(
+                    499,
+                    format!("Character in cooldown: {remaining:.2} seconds left."),
+                );
+            }
+        }
+
+        let mut character = character;
+        let outcome = match action {
+            "move" => self.action_move(&mut character, body)?,
+            "gathering" => self.action_gathering(&mut character)?,
+            "fight" => self.action_fight(&mut character)?,
+            "rest" => self.action_rest(&mut character),
+            "use" => self.action_use(&mut character, body)?,
+            "crafting" => self.action_crafting(&mut character, body)?,
+            "equip" => self.action_equip(&mut character, body)?,
+            "unequip" => self.action_unequip(&mut character, body)?,
+            "bank/deposit" => self.action_bank_deposit(&mut character, body)?,
+            "bank/withdraw" => self.action_bank_withdraw(&mut character, body)?,
+            "bank/deposit/gold" => self.action_bank_deposit_gold(&mut character, body)?,
+            "bank/withdraw/gold" => self.action_bank_withdraw_gold(&mut character, body)?,
+            "bank/buy_expansion" => self.action_bank_buy_expansion(&mut character)?,
+            "ge/buy" => self.action_ge_buy(&mut character, body)?,
+            "ge/sell" => self.action_ge_sell(&mut character, body)?,
+            "task/new" => self.action_task_new(&mut character)?,
+            "task/complete" => self.action_task_complete(&mut character)?,
+            "task/cancel" => self.action_task_cancel(&mut character)?,
+            "task/exchange" => self.action_task_exchange(&mut character)?,
+            "task/trade" => self.action_task_trade(&mut character, body)?,
+            _ => return fail(404, "Not found."),
+        };
+
+        let expiration = now + chrono::Duration::milliseconds((outcome.seconds * 1000.0) as i64);
+        let seconds = outcome.seconds.ceil() as i64;
+        character.cooldown = seconds;
+        character.cooldown_expiration = Some(expiration);
+
+        self.characters.insert(name.into(), character.clone());
+
+        let mut response = json!({
+            "cooldown": {
+                "totalSeconds": seconds,
+                "remainingSeconds": seconds,
+                "expiration": expiration,
+                "reason": outcome.reason,
+            },
+            "character": character,
+        });
+
+        if let (Some(response), Value::Object(extra)) = (response.as_object_mut(), outcome.extra) {
+            response.extend(extra);
+        }
+
+        Ok(data(response))
+    }
+
+    fn action_move(&self, character: &mut Character, body: &Value) -> Result<Outcome, Failure> {
+        let (x, y) = match (body["x"].as_i64(), body["y"].as_i64()) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return fail(422, "Invalid payload."),
+        };
+
+        let destination = match self.maps.iter().find(|map| map.x == x && map.y == y) {
+            Some(map) => map.clone(),
+            None => return fail(404, "Map not found."),
+        };
+
+        if (character.x, character.y) == (x, y) {
+            return fail(490, "Character already at destination.");
+        }
+
+        let distance = (character.x - x).abs() + (character.y - y).abs();
+        character.x = x;
+        character.y = y;
+
+        Ok(Outcome {
+            seconds: self.cooldowns.move_per_tile * distance as f64,
+            reason: "movement",
+            extra: json!({ "destination": destination }),
+        })
+    }
+
+    fn action_gathering(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        let resource = match self.content_at(character, "resource") {
+            Some(code) => self.get_resource(&code)?.clone(),
+            None => return fail(598, "Resource not found on this map."),
+        };
+
+        let level = skill_level(character, &resource.skill);
+        if level < resource.level {
+            return fail(493, "Not skill level required.");
+        }
+
+        if character.inventory.is_full() {
+            return fail(497, "Character inventory is full.");
+        }
+
+        let items = self.roll_drops(&resource.drops);
+        for item in &items {
+            character.inventory.add(&item.code, item.quantity);
+        }
+
+        let xp = 5 * resource.level + 5;
+        gain_skill_xp(character, &resource.skill, xp);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.gathering,
+            reason: "gathering",
+            extra: json!({ "details": { "xp": xp, "items": items } }),
+        })
+    }
+
+    fn action_fight(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        let monster = match self.content_at(character, "monster") {
+            Some(code) => self.get_monster(&code)?.clone(),
+            None => return fail(598, "Monster not found on this map."),
+        };
+
+        if character.inventory.is_full() {
+            return fail(497, "Character inventory is full.");
+        }
+
+        let combat::Outcome {
+            win: won,
+            turns,
+            hp,
+        } = combat::fight(character, &monster, &mut self.rng);
+
+        let (xp, gold, drops) = if won {
+            character.hp = hp;
+
+            let xp = 10 * monster.level;
+            let gold = self.rng.gen_range(monster.min_gold..=monster.max_gold);
+            let drops = self.roll_drops(&monster.drops);
+
+            gain_skill_xp(character, "combat", xp);
+            progress_task(character, "monsters", &monster.code, 1);
+            character.gold += gold;
+            for item in &drops {
+                character.inventory.add(&item.code, item.quantity);
+            }
+
+            (xp, gold, drops)
+        } else {
+            // the character respawns with full hp
+            character.hp = character.max_hp;
+            character.x = SPAWN.0;
+            character.y = SPAWN.1;
+
+            (0, 0, vec![])
+        };
+
+        let blocked = json!({ "fire": 0, "earth": 0, "water": 0, "air": 0, "total": 0 });
+
+        Ok(Outcome {
+            seconds: self.cooldowns.fight,
+            reason: "fight",
+            extra: json!({ "fight": {
+                "xp": xp,
+                "gold": gold,
+                "drops": drops,
+                "turns": turns,
+                "monster_blocked_hits": blocked,
+                "player_blocked_hits": blocked,
+                "logs": [],
+                "result": if won { "win" } else { "lose" },
+            }}),
+        })
+    }
+
+    fn action_rest(&self, character: &mut Character) -> Outcome {
+        let restored = character.max_hp - character.hp;
+        character.hp = character.max_hp;
+
+        Outcome {
+            seconds: (restored as f64 * self.cooldowns.rest_per_hp).max(self.cooldowns.rest),
+            reason: "rest",
+            extra: json!({ "hp_restored": restored }),
+        }
+    }
+
+    fn action_use(&self, character: &mut Character, body: &Value) -> Result<Outcome, Failure> {
+        let (code, quantity) = item_payload(body)?;
+        let item = self.get_item(&code)?.clone();
+
+        if item.kind != "consumable" {
+            return fail(476, "This item is not a consumable.");
+        }
+
+        if item.level > character.level {
+            return fail(496, "Character level is insufficient.");
+        }
+
+        if !character.inventory.remove(&code, quantity) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+
+        let heal: i64 = item
+            .effects
+            .iter()
+            .filter(|effect| effect.name == "heal")
+            .map(|effect| effect.value)
+            .sum();
+        character.hp = (character.hp + heal * quantity).min(character.max_hp);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.equip,
+            reason: "use",
+            extra: json!({ "item": item }),
+        })
+    }
+
+    fn action_crafting(&self, character: &mut Character, body: &Value) -> Result<Outcome, Failure> {
+        let (code, quantity) = item_payload(body)?;
+
+        let item = self.get_item(&code)?.clone();
+        let craft = match &item.craft {
+            Some(craft) => craft,
+            None => return fail(404, "Craft not found."),
+        };
+
+        if self.content_at(character, "workshop").as_deref() != Some(craft.skill.as_str()) {
+            return fail(598, "Workshop not found on this map.");
+        }
+
+        if skill_level(character, &craft.skill) < craft.level {
+            return fail(493, "Not skill level required.");
+        }
+
+        if craft.items.iter().any(|component| {
+            character.inventory.count(&component.code) < component.quantity * quantity
+        }) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+
+        for component in &craft.items {
+            character
+                .inventory
+                .remove(&component.code, component.quantity * quantity);
+        }
+
+        let crafted = craft.quantity * quantity;
+        if !character.inventory.add(&code, crafted) {
+            return fail(497, "Character inventory is full.");
+        }
+
+        let xp = (5 * craft.level + 5) * quantity;
+        gain_skill_xp(character, &craft.skill, xp);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.crafting * quantity as f64,
+            reason: "crafting",
+            extra: json!({ "details": {
+                "xp": xp,
+                "items": [{ "code": code, "quantity": crafted }],
+            }}),
+        })
+    }
+
+    fn action_equip(&self, character: &mut Character, body: &Value) -> Result<Outcome, Failure> {
+        let (code, slot) = match (
+            body["code"].as_str(),
+            body["slot"].as_str().and_then(Slot::from_code),
+        ) {
+            (Some(code), Some(slot)) => (code.to_string(), slot),
+            _ => return fail(422, "Invalid payload."),
+        };
+
+        let item = self.get_item(&code)?.clone();
+
+        if slot.item_type() != item.kind {
+            return fail(485, "This item can't be equipped in this slot.");
+        }
+
+        if item.level > character.level {
+            return fail(496, "Character level is insufficient.");
+        }
+
+        if !slot.equipped(character).is_empty() {
+            return fail(491, "Slot is not empty.");
+        }
+
+        if !character.inventory.remove(&code, 1) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+
+        *slot.equipped_mut(character) = code;
+        apply_effects(character, &item, 1);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.equip,
+            reason: "equip",
+            extra: json!({ "slot": slot.code(), "item": item }),
+        })
+    }
+
+    fn action_unequip(&self, character: &mut Character, body: &Value) -> Result<Outcome, Failure> {
+        let slot = match body["slot"].as_str().and_then(Slot::from_code) {
+            Some(slot) => slot,
+            None => return fail(422, "Invalid payload."),
+        };
+
+        let code = slot.equipped(character).to_string();
+        if code.is_empty() {
+            return fail(491, "Slot is empty.");
+        }
+
+        if !character.inventory.add(&code, 1) {
+            return fail(497, "Character inventory is full.");
+        }
+        slot.equipped_mut(character).clear();
+
+        let item = self.get_item(&code)?.clone();
+        apply_effects(character, &item, -1);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.equip,
+            reason: "unequip",
+            extra: json!({ "slot": slot.code(), "item": item }),
+        })
+    }
+
+    fn action_bank_deposit(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let (code, quantity) = item_payload(body)?;
+        let item = self.get_item(&code)?.clone();
+
+        if self.content_at(character, "bank").is_none() {
+            return fail(598, "Bank not found on this map.");
+        }
+
+        if !character.inventory.remove(&code, quantity) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+
+        add_to_bank(&mut self.bank, &code, quantity);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.bank,
+            reason: "deposit",
+            extra: json!({ "item": item, "bank": self.bank }),
+        })
+    }
+
+    fn action_bank_withdraw(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let (code, quantity) = item_payload(body)?;
+        let item = self.get_item(&code)?.clone();
+
+        if self.content_at(character, "bank").is_none() {
+            return fail(598, "Bank not found on this map.");
+        }
+
+        let in_bank = self
+            .bank
+            .iter()
+            .find(|item| item.code == code)
+            .map(|item| item.quantity)
+            .unwrap_or_default();
+        if in_bank < quantity {
+            return fail(404, "Item not found.");
+        }
+
+        if !character.inventory.add(&code, quantity) {
+            return fail(497, "Character inventory is full.");
+        }
+
+        add_to_bank(&mut self.bank, &code, -quantity);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.bank,
+            reason: "withdraw",
+            extra: json!({ "item": item, "bank": self.bank }),
+        })
+    }
+
+    fn action_bank_deposit_gold(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let quantity = gold_payload(body)?;
+
+        if self.content_at(character, "bank").is_none() {
+            return fail(598, "Bank not found on this map.");
+        }
+
+        if character.gold < quantity {
+            return fail(492, "Insufficient gold on your character.");
+        }
+
+        character.gold -= quantity;
+        self.bank_gold += quantity;
+
+        Ok(Outcome {
+            seconds: self.cooldowns.bank,
+            reason: "deposit_gold",
+            extra: json!({ "bank": { "quantity": self.bank_gold } }),
+        })
+    }
+
+    fn action_bank_withdraw_gold(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let quantity = gold_payload(body)?;
+
+        if self.content_at(character, "bank").is_none() {
+            return fail(598, "Bank not found on this map.");
+        }
+
+        if self.bank_gold < quantity {
+            return fail(460, "Insufficient gold in your bank.");
+        }
+
+        self.bank_gold -= quantity;
+        character.gold += quantity;
+
+        Ok(Outcome {
+            seconds: self.cooldowns.bank,
+            reason: "withdraw_gold",
+            extra: json!({ "bank": { "quantity": self.bank_gold } }),
+        })
+    }
+
+    fn action_bank_buy_expansion(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        if self.content_at(character, "bank").is_none() {
+            return fail(598, "Bank not found on this map.");
+        }
+
+        let cost = self.next_expansion_cost();
+        if character.gold < cost {
+            return fail(492, "Insufficient gold on your character.");
+        }
+
+        character.gold -= cost;
+        self.bank_expansions += 1;
+
+        Ok(Outcome {
+            seconds: self.cooldowns.bank,
+            reason: "buy_bank_expansion",
+            extra: json!({ "transaction": { "price": cost } }),
+        })
+    }
+
+    fn action_ge_buy(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let (code, quantity, price) = transaction_payload(body)?;
+
+        if self.content_at(character, "grand_exchange").is_none() {
+            return fail(598, "Grand Exchange not found on this map.");
+        }
+
+        let cooldown = self.cooldowns.bank;
+        let listing = self.get_listing(&code)?;
+
+        if listing.buy_price != Some(price) {
+            return fail(482, "No item at this price.");
+        }
+
+        if listing.stock < quantity {
+            return fail(480, "No stock for this item.");
+        }
+
+        if character.gold < price * quantity {
+            return fail(492, "Insufficient gold on your character.");
+        }
+
+        if !character.inventory.add(&code, quantity) {
+            return fail(497, "Character inventory is full.");
+        }
+
+        listing.stock -= quantity;
+        character.gold -= price * quantity;
+
+        Ok(Outcome {
+            seconds: cooldown,
+            reason: "buy_ge",
+            extra: json!({ "transaction": {
+                "code": code,
+                "quantity": quantity,
+                "price": price,
+                "total_price": price * quantity,
+            }}),
+        })
+    }
+
+    fn action_ge_sell(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let (code, quantity, price) = transaction_payload(body)?;
+
+        if self.content_at(character, "grand_exchange").is_none() {
+            return fail(598, "Grand Exchange not found on this map.");
+        }
+
+        let cooldown = self.cooldowns.bank;
+        let listing = self.get_listing(&code)?;
+
+        if listing.sell_price != Some(price) {
+            return fail(482, "No item at this price.");
+        }
+
+        if !character.inventory.remove(&code, quantity) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+
+        listing.stock += quantity;
+        character.gold += price * quantity;
+
+        Ok(Outcome {
+            seconds: cooldown,
+            reason: "sell_ge",
+            extra: json!({ "transaction": {
+                "code": code,
+                "quantity": quantity,
+                "price": price,
+                "total_price": price * quantity,
+            }}),
+        })
+    }
+
+    fn action_task_new(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        let kind = match self.content_at(character, "tasks_master") {
+            Some(kind) => kind,
+            None => return fail(598, "Tasks Master not found on this map."),
+        };
+
+        if !character.task.is_empty() {
+            return fail(489, "Character already has a task.");
+        }
+
+        // something the character can already do
+        let (codes, total) = match kind.as_str() {
+            "monsters" => (
+                self.monsters
+                    .iter()
+                    .filter(|monster| monster.level <= character.level)
+                    .map(|monster| monster.code.clone())
+                    .collect::<Vec<_>>(),
+                self.rng.gen_range(5..=10),
+            ),
+            _ => (
+                self.resources
+                    .iter()
+                    .filter(|resource| resource.level <= skill_level(character, &resource.skill))
+                    .flat_map(|resource| resource.drops.iter().map(|drop| drop.code.clone()))
+                    .collect(),
+                self.rng.gen_range(10..=20),
+            ),
+        };
+
+        if codes.is_empty() {
+            return fail(404, "Task not found.");
+        }
+        let code = codes[self.rng.gen_range(0..codes.len())].clone();
+
+        character.task = code.clone();
+        character.task_type = kind.clone();
+        character.task_progress = 0;
+        character.task_total = total;
+
+        Ok(Outcome {
+            seconds: self.cooldowns.task,
+            reason: "task",
+            extra: json!({ "task": { "code": code, "type": kind, "total": total } }),
+        })
+    }
+
+    fn action_task_complete(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        if self.content_at(character, "tasks_master").is_none() {
+            return fail(598, "Tasks Master not found on this map.");
+        }
+
+        if character.task.is_empty() {
+            return fail(487, "Character has no task.");
+        }
+
+        if character.task_progress < character.task_total {
+            return fail(488, "Character has not completed the task.");
+        }
+
+        if !character.inventory.add("tasks_coin", 1) {
+            return fail(497, "Character inventory is full.");
+        }
+        clear_task(character);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.task,
+            reason: "task",
+            extra: json!({ "reward": { "code": "tasks_coin", "quantity": 1 } }),
+        })
+    }
+
+    fn action_task_cancel(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        if self.content_at(character, "tasks_master").is_none() {
+            return fail(598, "Tasks Master not found on this map.");
+        }
+
+        if character.task.is_empty() {
+            return fail(487, "Character has no task.");
+        }
+
+        // cancelling costs a tasks coin
+        if !character.inventory.remove("tasks_coin", 1) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+        clear_task(character);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.task,
+            reason: "task",
+            extra: json!({}),
+        })
+    }
+
+    fn action_task_trade(
+        &mut self,
+        character: &mut Character,
+        body: &Value,
+    ) -> Result<Outcome, Failure> {
+        let (code, quantity) = item_payload(body)?;
+
+        if self.content_at(character, "tasks_master").as_deref() != Some("items") {
+            return fail(598, "Tasks Master not found on this map.");
+        }
+
+        if character.task_type != "items" || character.task != code {
+            return fail(474, "Character does not have this task.");
+        }
+
+        if character.task_progress + quantity > character.task_total {
+            return fail(475, "Task already completed or too many items submitted.");
+        }
+
+        if !character.inventory.remove(&code, quantity) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+        progress_task(character, "items", &code, quantity);
+
+        Ok(Outcome {
+            seconds: self.cooldowns.task,
+            reason: "task",
+            extra: json!({ "trade": { "code": code, "quantity": quantity } }),
+        })
+    }
+
+    fn action_task_exchange(&mut self, character: &mut Character) -> Result<Outcome, Failure> {
+        if self.content_at(character, "tasks_master").is_none() {
+            return fail(598, "Tasks Master not found on this map.");
+        }
+
+        if !character.inventory.remove("tasks_coin", TASK_EXCHANGE_COST) {
+            return fail(
+                478,
+                "Missing item or insufficient quantity in your inventory.",
+            );
+        }
+
+        let rewards = fixtures::task_rewards();
+        let (code, quantity) = rewards[self.rng.gen_range(0..rewards.len())];
+        if !character.inventory.add(code, quantity) {
+            return fail(497, "Character inventory is full.");
+        }
+
+        Ok(Outcome {
+            seconds: self.cooldowns.task,
+            reason: "task",
+            extra: json!({ "reward": { "code": code, "quantity": quantity } }),
+        })
+    }
+
+    /// Roll each drop, drop rates are 1 in `rate`
+    fn roll_drops(&mut self, drops: &[crate::response::DropRate]) -> Vec<SimpleItem> {
+        let mut items = vec![];
+
+        for drop in drops {
+            if self.rng.gen_range(0..drop.rate.max(1)) == 0 {
+                items.push(SimpleItem {
+                    code: drop.code.clone(),
+                    quantity: self.rng.gen_range(drop.min_quantity..=drop.max_quantity),
+                });
+            }
+        }
+
+        items
+    }
+}
+
+fn data(data: impl Serialize) -> Value {
+    json!({ "data": data })
+}
+
+/// A page of `items` as selected by the `page` and `size` query parameters
+fn page<'a, T: Serialize + 'a>(items: impl IntoIterator<Item = &'a T>, query: &str) -> Value {
+    let items: Vec<&T> = items.into_iter().collect();
+
+    let mut page = 1;
+    let mut size = 50;
+    for (key, value) in query.split('&').filter_map(|pair| pair.split_once('=')) {
+        match key {
+            "page" => page = value.parse().unwrap_or(page).max(1),
+            "size" => size = value.parse().unwrap_or(size).clamp(1, 100),
+            _ => {}
+        }
+    }
+
+    let total = items.len();
+    let pages = total.div_ceil(size).max(1);
+    let data: Vec<&T> = items
+        .into_iter()
+        .skip((page - 1) * size)
+        .take(size)
+        .collect();
+
+    json!({ "data": data, "total": total, "page": page, "size": size, "pages": pages })
+}
+
+fn item_payload(body: &Value) -> Result<(String, i64), Failure> {
+    match (body["code"].as_str(), body["quantity"].as_i64()) {
+        (Some(code), Some(quantity)) if quantity > 0 => Ok((code.into(), quantity)),
+        _ => fail(422, "Invalid payload."),
+    }
+}
+
+fn transaction_payload(body: &Value) -> Result<(String, i64, i64), Failure> {
+    let (code, quantity) = item_payload(body)?;
+
+    if quantity > MAX_TRANSACTION_QUANTITY {
+        return fail(479, "Too many items for a single transaction.");
+    }
+
+    match body["price"].as_i64() {
+        Some(price) if price > 0 => Ok((code, quantity, price)),
+        _ => fail(422, "Invalid payload."),
+    }
+}
+
+fn gold_payload(body: &Value) -> Result<i64, Failure> {
+    match body["quantity"].as_i64() {
+        Some(quantity) if quantity > 0 => Ok(quantity),
+        _ => fail(422, "Invalid payload."),
+    }
+}
+
+fn add_to_bank(bank: &mut Vec<SimpleItem>, code: &str, quantity: i64) {
+    match bank.iter_mut().find(|item| item.code == code) {
+        Some(item) => item.quantity += quantity,
+        None => bank.push(SimpleItem {
+            code: code.into(),
+            quantity,
+        }),
+    }
+
+    bank.retain(|item| item.quantity > 0);
+}
+
+/// Count `quantity` of `code` towards the character's task if it is of type `kind`
+fn progress_task(character: &mut Character, kind: &str, code: &str, quantity: i64) {
+    if character.task_type == kind && character.task == code {
+        character.task_progress = (character.task_progress + quantity).min(character.task_total);
+    }
+}
+
+fn clear_task(character: &mut Character) {
+    character.task.clear();
+    character.task_type.clear();
+    character.task_progress = 0;
+    character.task_total = 0;
+}
+
+fn skill_level(character: &Character, skill: &str) -> i64 {
+    match skill {
+        "mining" => character.mining_level,
+        "woodcutting" => character.woodcutting_level,
+        "fishing" => character.fishing_level,
+        "weaponcrafting" => character.weaponcrafting_level,
+        "gearcrafting" => character.gearcrafting_level,
+        "jewelrycrafting" => character.jewelrycrafting_level,
+        "cooking" => character.cooking_level,
+        _ => character.level,
+    }
+}
+
+fn gain_skill_xp(character: &mut Character, skill: &str, xp: i64) {
+    let (level, current, max) = match skill {
+        "mining" => (
+            &mut character.mining_level,
+            &mut character.mining_xp,
+            &mut character.mining_max_xp,
+        ),
+        "woodcutting" => (
+            &mut character.woodcutting_level,
+            &mut character.woodcutting_xp,
+            &mut character.woodcutting_max_xp,
+        ),
+        "fishing" => (
+            &mut character.fishing_level,
+            &mut character.fishing_xp,
+            &mut character.fishing_max_xp,
+        ),
+        "weaponcrafting" => (
+            &mut character.weaponcrafting_level,
+            &mut character.weaponcrafting_xp,
+            &mut character.weaponcrafting_max_xp,
+        ),
+        "gearcrafting" => (
+            &mut character.gearcrafting_level,
+            &mut character.gearcrafting_xp,
+            &mut character.gearcrafting_max_xp,
+        ),
+        "jewelrycrafting" => (
+            &mut character.jewelrycrafting_level,
+            &mut character.jewelrycrafting_xp,
+            &mut character.jewelrycrafting_max_xp,
+        ),
+        "cooking" => (
+            &mut character.cooking_level,
+            &mut character.cooking_xp,
+            &mut character.cooking_max_xp,
+        ),
+        _ => (
+            &mut character.level,
+            &mut character.xp,
+            &mut character.max_xp,
+        ),
+    };
+
+    let mut levels = 0;
+    *current += xp;
+    while *current >= *max {
+        *current -= *max;
+        *level += 1;
+        *max = 150 * *level;
+        levels += 1;
+    }
+
+    // combat levels give more hp
+    if skill == "combat" {
+        character.max_hp += 5 * levels;
+    }
+
+    character.total_xp += xp;
+}
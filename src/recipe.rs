@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::Error,
+    response::{DropRate, Item, Monster, Resource},
+};
+
+// Guards against recipes that (indirectly) require themselves
+const MAX_DEPTH: usize = 16;
+
+/// A step towards obtaining an item
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Withdraw {
+        code: String,
+        quantity: i64,
+    },
+    /// Gather `resource` until `quantity` more of `code` has dropped
+    Gather {
+        resource: String,
+        code: String,
+        quantity: i64,
+    },
+    /// Fight `monster` until `quantity` more of `code` has dropped
+    Fight {
+        monster: String,
+        code: String,
+        quantity: i64,
+    },
+    Craft {
+        code: String,
+        quantity: i64,
+    },
+}
+
+/// Expands the crafting tree of an item into the steps needed to obtain it, using what is
+/// already in the inventory and bank before gathering, fighting or crafting the rest.
+pub struct Planner<'a> {
+    items: &'a HashMap<String, Item>,
+    resources: &'a [Resource],
+    monsters: &'a [Monster],
+    inventory: HashMap<String, i64>,
+    bank: HashMap<String, i64>,
+    withdrawals: Vec<(String, i64)>,
+    steps: Vec<Step>,
+}
+
+impl<'a> Planner<'a> {
+    pub fn new(
+        items: &'a HashMap<String, Item>,
+        resources: &'a [Resource],
+        monsters: &'a [Monster],
+        inventory: HashMap<String, i64>,
+        bank: HashMap<String, i64>,
+    ) -> Self {
+        Self {
+            items,
+            resources,
+            monsters,
+            inventory,
+            bank,
+            withdrawals: vec![],
+            steps: vec![],
+        }
+    }
+
+    /// Plan the steps to have `quantity` of `code` in the inventory. Withdrawals come first
+    /// so they can be done in a single trip to the bank.
+    pub fn plan(mut self, code: &str, quantity: i64) -> Result<Vec<Step>, Error> {
+        self.expand(code, quantity, 0)?;
+
+        let mut steps: Vec<Step> = self
+            .withdrawals
+            .into_iter()
+            .map(|(code, quantity)| Step::Withdraw { code, quantity })
+            .collect();
+        steps.extend(self.steps);
+
+        Ok(steps)
+    }
+
+    fn expand(&mut self, code: &str, quantity: i64, depth: usize) -> Result<(), Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::Unobtainable(code.into()));
+        }
+
+        let mut missing = quantity - take(&mut self.inventory, code, quantity);
+        if missing == 0 {
+            return Ok(());
+        }
+
+        let withdrawn = take(&mut self.bank, code, missing);
+        if withdrawn > 0 {
+            match self.withdrawals.iter_mut().find(|(c, _)| c == code) {
+                Some((_, quantity)) => *quantity += withdrawn,
+                None => self.withdrawals.push((code.into(), withdrawn)),
+            }
+
+            missing -= withdrawn;
+            if missing == 0 {
+                return Ok(());
+            }
+        }
+
+        let items = self.items;
+        if let Some(craft) = items.get(code).and_then(|item| item.craft.as_ref()) {
+            let crafts = (missing + craft.quantity - 1) / craft.quantity;
+
+            for component in &craft.items {
+                self.expand(&component.code, component.quantity * crafts, depth + 1)?;
+            }
+
+            self.steps.push(Step::Craft {
+                code: code.into(),
+                quantity: crafts,
+            });
+
+            // recipes that make more than one item can leave some over
+            let surplus = crafts * craft.quantity - missing;
+            if surplus > 0 {
+                *self.inventory.entry(code.into()).or_default() += surplus;
+            }
+
+            return Ok(());
+        }
+
+        if let Some(resource) = best_source(self.resources, code, |r| &r.drops) {
+            self.steps.push(Step::Gather {
+                resource: resource.code.clone(),
+                code: code.into(),
+                quantity: missing,
+            });
+
+            return Ok(());
+        }
+
+        if let Some(monster) = best_source(self.monsters, code, |m| &m.drops) {
+            self.steps.push(Step::Fight {
+                monster: monster.code.clone(),
+                code: code.into(),
+                quantity: missing,
+            });
+
+            return Ok(());
+        }
+
+        Err(Error::Unobtainable(code.into()))
+    }
+}
+
+/// How many items the steps bring into the inventory, before crafting uses any of them up
+pub fn intake(steps: &[Step]) -> i64 {
+    steps
+        .iter()
+        .map(|step| match step {
+            Step::Withdraw { quantity, .. }
+            | Step::Gather { quantity, .. }
+            | Step::Fight { quantity, .. } => *quantity,
+            Step::Craft { .. } => 0,
+        })
+        .sum()
+}
+
+/// Take up to `quantity` of `code` from `counts`, returning how many were taken
+pub(crate) fn take(counts: &mut HashMap<String, i64>, code: &str, quantity: i64) -> i64 {
+    match counts.get_mut(code) {
+        Some(available) => {
+            let taken = quantity.min(*available);
+            *available -= taken;
+            taken
+        }
+        None => 0,
+    }
+}
+
+/// The source most likely to drop `code`, drop rates are 1 in `rate`
+fn best_source<'s, T>(
+    sources: &'s [T],
+    code: &str,
+    drops: impl Fn(&T) -> &Vec<DropRate>,
+) -> Option<&'s T> {
+    sources
+        .iter()
+        .filter_map(|source| {
+            drops(source)
+                .iter()
+                .find(|drop| drop.code == code)
+                .map(|drop| (source, drop.rate))
+        })
+        .min_by_key(|(_, rate)| *rate)
+        .map(|(source, _)| source)
+}
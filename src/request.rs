@@ -0,0 +1,149 @@
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoveRequest {
+    pub x: i64,
+    pub y: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CraftingRequest {
+    pub code: String,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EquipRequest {
+    pub code: String,
+    pub slot: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnequipRequest {
+    pub slot: String,
+}
+
+/// Item code and quantity, used for bank deposits, withdrawals, recycling and task trades
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimpleItemRequest {
+    pub code: String,
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoldRequest {
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GETransactionRequest {
+    pub code: String,
+    pub quantity: i64,
+    pub price: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AddCharacterRequest {
+    pub name: String,
+    pub skin: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeleteCharacterRequest {
+    pub name: String,
+}
+
+/// Query parameters for paginated endpoints
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Page {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<i64>,
+}
+
+/// An action performed by a character, executed through `my/{name}/action/...`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Move(MoveRequest),
+    Fight,
+    Gathering,
+    Rest,
+    Use(SimpleItemRequest),
+    Crafting(CraftingRequest),
+    Equip(EquipRequest),
+    Unequip(UnequipRequest),
+    BankDeposit(SimpleItemRequest),
+    BankDepositGold(GoldRequest),
+    BankWithdraw(SimpleItemRequest),
+    BankWithdrawGold(GoldRequest),
+    BankBuyExpansion,
+    Recycling(SimpleItemRequest),
+    GEBuy(GETransactionRequest),
+    GESell(GETransactionRequest),
+    TaskNew,
+    TaskComplete,
+    TaskExchange,
+    TaskCancel,
+    TaskTrade(SimpleItemRequest),
+}
+
+impl Action {
+    /// The path of the action under `my/{name}/action/`, e.g. `bank/deposit`
+    pub fn path(&self) -> &'static str {
+        match self {
+            Action::Move(_) => "move",
+            Action::Fight => "fight",
+            Action::Gathering => "gathering",
+            Action::Rest => "rest",
+            Action::Use(_) => "use",
+            Action::Crafting(_) => "crafting",
+            Action::Equip(_) => "equip",
+            Action::Unequip(_) => "unequip",
+            Action::BankDeposit(_) => "bank/deposit",
+            Action::BankDepositGold(_) => "bank/deposit/gold",
+            Action::BankWithdraw(_) => "bank/withdraw",
+            Action::BankWithdrawGold(_) => "bank/withdraw/gold",
+            Action::BankBuyExpansion => "bank/buy_expansion",
+            Action::Recycling(_) => "recycling",
+            Action::GEBuy(_) => "ge/buy",
+            Action::GESell(_) => "ge/sell",
+            Action::TaskNew => "task/new",
+            Action::TaskComplete => "task/complete",
+            Action::TaskExchange => "task/exchange",
+            Action::TaskCancel => "task/cancel",
+            Action::TaskTrade(_) => "task/trade",
+        }
+    }
+
+    /// The JSON request body, `None` for actions without one
+    pub fn body(&self) -> Option<Value> {
+        let body = match self {
+            Action::Move(body) => serde_json::to_value(body),
+            Action::Use(body)
+            | Action::BankDeposit(body)
+            | Action::BankWithdraw(body)
+            | Action::Recycling(body)
+            | Action::TaskTrade(body) => serde_json::to_value(body),
+            Action::Crafting(body) => serde_json::to_value(body),
+            Action::Equip(body) => serde_json::to_value(body),
+            Action::Unequip(body) => serde_json::to_value(body),
+            Action::BankDepositGold(body) | Action::BankWithdrawGold(body) => {
+                serde_json::to_value(body)
+            }
+            Action::GEBuy(body) | Action::GESell(body) => serde_json::to_value(body),
+            Action::Fight
+            | Action::Gathering
+            | Action::Rest
+            | Action::BankBuyExpansion
+            | Action::TaskNew
+            | Action::TaskComplete
+            | Action::TaskExchange
+            | Action::TaskCancel => return None,
+        };
+
+        // the requests are plain structs, which always serialize
+        body.ok()
+    }
+}
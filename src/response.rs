@@ -1,14 +1,21 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
 use std::fmt;
 use thiserror::Error;
 
+use crate::inventory::Inventory;
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct APIResponse<T> {
     pub data: Option<T>,
     pub error: Option<ErrorData>,
+    pub total: Option<i64>,
+    pub page: Option<i64>,
+    pub size: Option<i64>,
+    pub pages: Option<i64>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, Error)]
@@ -30,6 +37,17 @@ pub struct ActionData {
     pub cooldown: Cooldown,
     pub destination: Option<Destination>,
     pub character: Character,
+    pub fight: Option<Fight>,
+    pub details: Option<SkillDetails>,
+    pub item: Option<Item>,
+    pub bank: Option<BankUpdate>,
+    pub slot: Option<String>,
+    pub transaction: Option<Transaction>,
+    pub task: Option<TaskAssignment>,
+    pub reward: Option<TaskReward>,
+    pub trade: Option<SimpleItem>,
+    #[serde(rename = "hp_restored")]
+    pub hp_restored: Option<i64>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -37,7 +55,7 @@ pub struct ActionData {
 pub struct Cooldown {
     pub total_seconds: i64,
     pub remaining_seconds: i64,
-    pub expiration: String,
+    pub expiration: DateTime<Utc>,
     pub reason: String,
 }
 
@@ -106,6 +124,9 @@ pub struct Character {
     #[serde(rename = "cooking_max_xp")]
     pub cooking_max_xp: i64,
     pub hp: i64,
+    /// Missing from older versions of the API, where characters always fight at full hp
+    #[serde(rename = "max_hp", default)]
+    pub max_hp: i64,
     pub haste: i64,
     #[serde(rename = "critical_strike")]
     pub critical_strike: i64,
@@ -138,7 +159,7 @@ pub struct Character {
     pub y: i64,
     pub cooldown: i64,
     #[serde(rename = "cooldown_expiration")]
-    pub cooldown_expiration: String,
+    pub cooldown_expiration: Option<DateTime<Utc>>,
     #[serde(rename = "weapon_slot")]
     pub weapon_slot: String,
     #[serde(rename = "shield_slot")]
@@ -171,88 +192,9 @@ pub struct Character {
     pub consumable2_slot: String,
     #[serde(rename = "consumable2_slot_quantity")]
     pub consumable2_slot_quantity: i64,
-    #[serde(rename = "inventory_slot1")]
-    pub inventory_slot1: String,
-    #[serde(rename = "inventory_slot1_quantity")]
-    pub inventory_slot1_quantity: i64,
-    #[serde(rename = "inventory_slot2")]
-    pub inventory_slot2: String,
-    #[serde(rename = "inventory_slot2_quantity")]
-    pub inventory_slot2_quantity: i64,
-    #[serde(rename = "inventory_slot3")]
-    pub inventory_slot3: String,
-    #[serde(rename = "inventory_slot3_quantity")]
-    pub inventory_slot3_quantity: i64,
-    #[serde(rename = "inventory_slot4")]
-    pub inventory_slot4: String,
-    #[serde(rename = "inventory_slot4_quantity")]
-    pub inventory_slot4_quantity: i64,
-    #[serde(rename = "inventory_slot5")]
-    pub inventory_slot5: String,
-    #[serde(rename = "inventory_slot5_quantity")]
-    pub inventory_slot5_quantity: i64,
-    #[serde(rename = "inventory_slot6")]
-    pub inventory_slot6: String,
-    #[serde(rename = "inventory_slot6_quantity")]
-    pub inventory_slot6_quantity: i64,
-    #[serde(rename = "inventory_slot7")]
-    pub inventory_slot7: String,
-    #[serde(rename = "inventory_slot7_quantity")]
-    pub inventory_slot7_quantity: i64,
-    #[serde(rename = "inventory_slot8")]
-    pub inventory_slot8: String,
-    #[serde(rename = "inventory_slot8_quantity")]
-    pub inventory_slot8_quantity: i64,
-    #[serde(rename = "inventory_slot9")]
-    pub inventory_slot9: String,
-    #[serde(rename = "inventory_slot9_quantity")]
-    pub inventory_slot9_quantity: i64,
-    #[serde(rename = "inventory_slot10")]
-    pub inventory_slot10: String,
-    #[serde(rename = "inventory_slot10_quantity")]
-    pub inventory_slot10_quantity: i64,
-    #[serde(rename = "inventory_slot11")]
-    pub inventory_slot11: String,
-    #[serde(rename = "inventory_slot11_quantity")]
-    pub inventory_slot11_quantity: i64,
-    #[serde(rename = "inventory_slot12")]
-    pub inventory_slot12: String,
-    #[serde(rename = "inventory_slot12_quantity")]
-    pub inventory_slot12_quantity: i64,
-    #[serde(rename = "inventory_slot13")]
-    pub inventory_slot13: String,
-    #[serde(rename = "inventory_slot13_quantity")]
-    pub inventory_slot13_quantity: i64,
-    #[serde(rename = "inventory_slot14")]
-    pub inventory_slot14: String,
-    #[serde(rename = "inventory_slot14_quantity")]
-    pub inventory_slot14_quantity: i64,
-    #[serde(rename = "inventory_slot15")]
-    pub inventory_slot15: String,
-    #[serde(rename = "inventory_slot15_quantity")]
-    pub inventory_slot15_quantity: i64,
-    #[serde(rename = "inventory_slot16")]
-    pub inventory_slot16: String,
-    #[serde(rename = "inventory_slot16_quantity")]
-    pub inventory_slot16_quantity: i64,
-    #[serde(rename = "inventory_slot17")]
-    pub inventory_slot17: String,
-    #[serde(rename = "inventory_slot17_quantity")]
-    pub inventory_slot17_quantity: i64,
-    #[serde(rename = "inventory_slot18")]
-    pub inventory_slot18: String,
-    #[serde(rename = "inventory_slot18_quantity")]
-    pub inventory_slot18_quantity: i64,
-    #[serde(rename = "inventory_slot19")]
-    pub inventory_slot19: String,
-    #[serde(rename = "inventory_slot19_quantity")]
-    pub inventory_slot19_quantity: i64,
-    #[serde(rename = "inventory_slot20")]
-    pub inventory_slot20: String,
-    #[serde(rename = "inventory_slot20_quantity")]
-    pub inventory_slot20_quantity: i64,
-    #[serde(rename = "inventory_max_items")]
-    pub inventory_max_items: i64,
+    /// The `inventory` array, or the `inventory_slot` fields of older versions of the API
+    #[serde(flatten)]
+    pub inventory: Inventory,
     pub task: String,
     #[serde(rename = "task_type")]
     pub task_type: String,
@@ -262,8 +204,292 @@ pub struct Character {
     pub task_total: i64,
 }
 
-pub struct InventorySlot {
-    pub name: String,
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimpleItem {
+    pub code: String,
+    pub quantity: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gold {
+    pub quantity: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FightResult {
+    Win,
+    Lose,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedHits {
+    pub fire: i64,
+    pub earth: i64,
+    pub water: i64,
+    pub air: i64,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fight {
+    pub xp: i64,
+    pub gold: i64,
+    pub drops: Vec<SimpleItem>,
+    pub turns: i64,
+    #[serde(rename = "monster_blocked_hits")]
+    pub monster_blocked_hits: BlockedHits,
+    #[serde(rename = "player_blocked_hits")]
+    pub player_blocked_hits: BlockedHits,
+    pub logs: Vec<String>,
+    pub result: FightResult,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkillDetails {
+    pub xp: i64,
+    pub items: Vec<SimpleItem>,
+}
+
+/// The bank contents returned by bank actions, items for item deposits and
+/// withdrawals, gold for gold deposits and withdrawals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BankUpdate {
+    Items(Vec<SimpleItem>),
+    Gold(Gold),
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub code: Option<String>,
+    pub quantity: Option<i64>,
+    pub price: i64,
+    #[serde(rename = "total_price")]
+    pub total_price: Option<i64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAssignment {
+    pub code: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub total: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskReward {
+    pub code: String,
+    pub quantity: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub status: String,
+    pub version: String,
+    #[serde(rename = "characters_online")]
+    pub characters_online: i64,
+    #[serde(rename = "server_time")]
+    pub server_time: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MapContent {
+    #[serde(rename = "type")]
+    pub kind: String,
     pub code: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Map {
+    pub name: String,
+    pub skin: String,
+    pub x: i64,
+    pub y: i64,
+    pub content: Option<MapContent>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemEffect {
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Craft {
+    pub skill: String,
+    pub level: i64,
+    pub items: Vec<SimpleItem>,
     pub quantity: i64,
 }
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub name: String,
+    pub code: String,
+    pub level: i64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub subtype: String,
+    pub description: String,
+    #[serde(default)]
+    pub effects: Vec<ItemEffect>,
+    pub craft: Option<Craft>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SingleItem {
+    pub item: Item,
+    pub ge: Option<GEItem>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DropRate {
+    pub code: String,
+    pub rate: i64,
+    #[serde(rename = "min_quantity")]
+    pub min_quantity: i64,
+    #[serde(rename = "max_quantity")]
+    pub max_quantity: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Monster {
+    pub name: String,
+    pub code: String,
+    pub level: i64,
+    pub hp: i64,
+    #[serde(rename = "attack_fire")]
+    pub attack_fire: i64,
+    #[serde(rename = "attack_earth")]
+    pub attack_earth: i64,
+    #[serde(rename = "attack_water")]
+    pub attack_water: i64,
+    #[serde(rename = "attack_air")]
+    pub attack_air: i64,
+    #[serde(rename = "res_fire")]
+    pub res_fire: i64,
+    #[serde(rename = "res_earth")]
+    pub res_earth: i64,
+    #[serde(rename = "res_water")]
+    pub res_water: i64,
+    #[serde(rename = "res_air")]
+    pub res_air: i64,
+    #[serde(rename = "min_gold")]
+    pub min_gold: i64,
+    #[serde(rename = "max_gold")]
+    pub max_gold: i64,
+    pub drops: Vec<DropRate>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub name: String,
+    pub code: String,
+    pub skill: String,
+    pub level: i64,
+    pub drops: Vec<DropRate>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    pub name: String,
+    pub map: Map,
+    #[serde(rename = "previous_skin")]
+    pub previous_skin: String,
+    pub duration: i64,
+    pub expiration: String,
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GEItem {
+    pub code: String,
+    pub stock: i64,
+    #[serde(rename = "sell_price")]
+    pub sell_price: Option<i64>,
+    #[serde(rename = "buy_price")]
+    pub buy_price: Option<i64>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDefinition {
+    pub code: String,
+    pub level: i64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(rename = "min_quantity")]
+    pub min_quantity: i64,
+    #[serde(rename = "max_quantity")]
+    pub max_quantity: i64,
+    pub skill: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRewardDefinition {
+    pub code: String,
+    #[serde(rename = "min_quantity")]
+    pub min_quantity: i64,
+    #[serde(rename = "max_quantity")]
+    pub max_quantity: i64,
+    pub odds: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Achievement {
+    pub name: String,
+    pub code: String,
+    pub description: String,
+    pub points: i64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub target: Option<String>,
+    pub total: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub skin: String,
+    #[serde(default)]
+    pub level: i64,
+    #[serde(rename = "total_xp", default)]
+    pub total_xp: i64,
+    #[serde(default)]
+    pub gold: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankDetails {
+    pub slots: i64,
+    pub expansions: i64,
+    #[serde(rename = "next_expansion_cost")]
+    pub next_expansion_cost: i64,
+    pub gold: i64,
+}
@@ -0,0 +1,198 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    client::Client, clock::Clock, config::Config, error::Error, game_data::GameData, mock::World,
+    response, supervisor::Supervisor, Character,
+};
+
+/// What a character achieved during a simulation
+#[derive(Debug, Clone)]
+pub struct CharacterReport {
+    pub name: String,
+    /// XP gained across all skills
+    pub xp: i64,
+    pub gold: i64,
+    /// Change in the character's inventory
+    pub items: BTreeMap<String, i64>,
+    pub restarts: u64,
+    /// Time between the end of the character's last action and the end of the simulation,
+    /// a long idle time means its tasks got stuck
+    pub idle: Duration,
+}
+
+/// The outcome of running a config against a simulated world
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub duration: Duration,
+    pub characters: Vec<CharacterReport>,
+    /// Change in the bank
+    pub bank: BTreeMap<String, i64>,
+    pub bank_gold: i64,
+}
+
+impl Report {
+    fn hours(&self) -> f64 {
+        self.duration.as_secs_f64() / 3600.0
+    }
+
+    pub fn per_hour(&self, amount: i64) -> f64 {
+        amount as f64 / self.hours()
+    }
+
+    /// Items gained by every character together, in their inventories and the bank
+    pub fn items(&self) -> BTreeMap<String, i64> {
+        let mut items = self.bank.clone();
+
+        for character in &self.characters {
+            for (code, quantity) in &character.items {
+                *items.entry(code.clone()).or_default() += quantity;
+            }
+        }
+
+        items.retain(|_, quantity| *quantity != 0);
+        items
+    }
+
+    pub fn gold(&self) -> i64 {
+        self.bank_gold + self.characters.iter().map(|c| c.gold).sum::<i64>()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Simulated {:.1} hours", self.hours())?;
+
+        for character in &self.characters {
+            writeln!(
+                f,
+                "{}: {:.0} xp/h, {:.0} gold/h, {} restarts, idle for {}s at the end",
+                character.name,
+                self.per_hour(character.xp),
+                self.per_hour(character.gold),
+                character.restarts,
+                character.idle.as_secs(),
+            )?;
+        }
+
+        writeln!(f, "gold: {:.0}/h", self.per_hour(self.gold()))?;
+        for (code, quantity) in self.items() {
+            writeln!(f, "{code}: {:.1}/h", self.per_hour(quantity))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Run the tasks in `config` against a simulated world for `duration` of game time. Time is
+/// virtual, so this takes far less than `duration`, and drops are seeded with `seed` so the
+/// same config always gives the same report.
+pub fn simulate(config: &Config, duration: Duration, seed: u64) -> Result<Report, Error> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .start_paused(true)
+        .build()?;
+
+    runtime.block_on(run(config, duration, seed))
+}
+
+async fn run(config: &Config, duration: Duration, seed: u64) -> Result<Report, Error> {
+    let mut world = World::new(seed);
+    for character in &config.characters {
+        world.add_character(&character.name);
+    }
+
+    let world = Arc::new(Mutex::new(world));
+    let clock = Clock::new();
+    let client = Client::local(world.clone(), clock.clone());
+    let game = Arc::new(GameData::new(client.clone()));
+
+    let (start, start_bank, start_bank_gold) = snapshot(&world);
+
+    let supervisor = Supervisor::new(config.retry.clone());
+    for character in &config.characters {
+        let loaded = Character::load(client.clone(), character.name.clone(), game.clone()).await?;
+
+        let loaded = loaded
+            .with_heal_policy(config.heal.clone())
+            .with_deposit_policy(
+                character
+                    .deposit
+                    .clone()
+                    .unwrap_or_else(|| config.deposit.clone()),
+            );
+
+        supervisor.supervise(Arc::new(loaded), character.tasks.clone());
+    }
+
+    tokio::time::sleep(duration).await;
+
+    let (end, end_bank, end_bank_gold) = snapshot(&world);
+    let health = supervisor.health();
+    let now = clock.now();
+
+    let characters = start
+        .iter()
+        .zip(&end)
+        .map(|(start, end)| CharacterReport {
+            name: end.name.clone(),
+            xp: end.total_xp - start.total_xp,
+            gold: end.gold - start.gold,
+            items: difference(&inventory(start), &inventory(end)),
+            restarts: health
+                .get(&end.name)
+                .map(|h| h.restarts)
+                .unwrap_or_default(),
+            idle: end
+                .cooldown_expiration
+                // still on cooldown means the character is busy
+                .map(|expiration| (now - expiration).to_std().unwrap_or(Duration::ZERO))
+                .unwrap_or(duration),
+        })
+        .collect();
+
+    Ok(Report {
+        duration,
+        characters,
+        bank: difference(&start_bank, &end_bank),
+        bank_gold: end_bank_gold - start_bank_gold,
+    })
+}
+
+fn snapshot(world: &Mutex<World>) -> (Vec<response::Character>, BTreeMap<String, i64>, i64) {
+    let world = world.lock().expect("world lock poisoned");
+
+    let characters = world.characters().cloned().collect();
+    let bank = world
+        .bank()
+        .iter()
+        .map(|item| (item.code.clone(), item.quantity))
+        .collect();
+
+    (characters, bank, world.bank_gold())
+}
+
+fn inventory(character: &response::Character) -> BTreeMap<String, i64> {
+    let mut items = BTreeMap::new();
+
+    for slot in character.inventory.items() {
+        *items.entry(slot.code.clone()).or_default() += slot.quantity;
+    }
+
+    items
+}
+
+fn difference(start: &BTreeMap<String, i64>, end: &BTreeMap<String, i64>) -> BTreeMap<String, i64> {
+    let mut difference = end.clone();
+
+    for (code, quantity) in start {
+        *difference.entry(code.clone()).or_default() -= quantity;
+    }
+
+    difference.retain(|_, quantity| *quantity != 0);
+    difference
+}
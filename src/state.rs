@@ -0,0 +1,27 @@
+use std::sync::{Arc, RwLock};
+
+use crate::response;
+
+/// Latest known state of a character, refreshed from every action response
+#[derive(Clone)]
+pub struct State(Arc<RwLock<response::Character>>);
+
+impl State {
+    pub fn new(character: response::Character) -> Self {
+        Self(Arc::new(RwLock::new(character)))
+    }
+
+    /// A snapshot of the character state
+    pub fn get(&self) -> response::Character {
+        self.read(|character| character.clone())
+    }
+
+    /// Read from the character state without cloning it
+    pub fn read<R>(&self, f: impl FnOnce(&response::Character) -> R) -> R {
+        f(&self.0.read().expect("state lock poisoned"))
+    }
+
+    pub fn set(&self, character: response::Character) {
+        *self.0.write().expect("state lock poisoned") = character;
+    }
+}
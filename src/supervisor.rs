@@ -0,0 +1,346 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::{
+    sync::watch,
+    task::{AbortHandle, JoinError},
+};
+
+use crate::{
+    conditions,
+    config::{RetryPolicy, TaskConfig},
+    error::Error,
+    tasks, Character,
+};
+
+// Pause after a pass over the tasks where none of their conditions held, so we don't spin
+const IDLE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    /// Waiting to restart after a failure
+    Restarting,
+    /// Not running any tasks, e.g. because none are configured
+    Idle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncidentKind {
+    Error,
+    Panic,
+}
+
+#[derive(Debug, Clone)]
+pub struct Incident {
+    pub at: DateTime<Utc>,
+    pub kind: IncidentKind,
+    /// The task that failed, `None` for the execution loop
+    pub task: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CharacterHealth {
+    pub status: Status,
+    pub restarts: u64,
+    pub consecutive_failures: u32,
+    pub last_incident: Option<Incident>,
+}
+
+impl Default for CharacterHealth {
+    fn default() -> Self {
+        Self {
+            status: Status::Running,
+            restarts: 0,
+            consecutive_failures: 0,
+            last_incident: None,
+        }
+    }
+}
+
+type Health = Arc<RwLock<HashMap<String, CharacterHealth>>>;
+
+type Tasks = Arc<Vec<TaskConfig>>;
+
+/// Runs each character's task loop and execution loop, restarting them with a backoff when
+/// a task returns an error or either loop panics
+#[derive(Clone)]
+pub struct Supervisor {
+    policy: RetryPolicy,
+    health: Health,
+    // the task list of each supervised character, dropped to stop the character
+    tasks: Arc<Mutex<HashMap<String, watch::Sender<Tasks>>>>,
+}
+
+impl Supervisor {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            health: Arc::default(),
+            tasks: Arc::default(),
+        }
+    }
+
+    /// Names of the supervised characters
+    pub fn characters(&self) -> Vec<String> {
+        self.tasks
+            .lock()
+            .expect("tasks lock poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Replace a character's tasks once the task it is running finishes. Returns false if the
+    /// character isn't supervised.
+    pub fn set_tasks(&self, name: &str, tasks: Vec<TaskConfig>) -> bool {
+        match self.tasks.lock().expect("tasks lock poisoned").get(name) {
+            Some(sender) => {
+                sender.send_replace(Arc::new(tasks));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop a character once the task it is running finishes. Returns false if the character
+    /// isn't supervised.
+    pub fn stop(&self, name: &str) -> bool {
+        self.tasks
+            .lock()
+            .expect("tasks lock poisoned")
+            .remove(name)
+            .is_some()
+    }
+
+    /// Health of every supervised character
+    pub fn health(&self) -> HashMap<String, CharacterHealth> {
+        self.health.read().expect("health lock poisoned").clone()
+    }
+
+    /// True if no supervised character is restarting after a failure
+    pub fn is_healthy(&self) -> bool {
+        self.health
+            .read()
+            .expect("health lock poisoned")
+            .values()
+            .all(|health| health.status != Status::Restarting)
+    }
+
+    /// Spawn the task loop and execution loop of a character
+    pub fn supervise(&self, character: Arc<Character>, tasks: Vec<TaskConfig>) {
+        let name = character.name().to_string();
+        self.update(&name, |_| {});
+
+        let (sender, receiver) = watch::channel(Arc::new(tasks));
+        self.tasks
+            .lock()
+            .expect("tasks lock poisoned")
+            .insert(name.clone(), sender);
+
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            let execution = {
+                let supervisor = supervisor.clone();
+                let character = character.clone();
+
+                tokio::spawn(async move { supervisor.execution_loop(character).await })
+            };
+
+            supervisor.task_loop(character, receiver).await;
+            execution.abort();
+
+            tracing::info!(character = %name, "character stopped");
+            if !supervisor.characters().contains(&name) {
+                let mut health = supervisor.health.write().expect("health lock poisoned");
+                health.remove(&name);
+            }
+        });
+    }
+
+    /// Run the character's tasks until it is stopped, picking up new tasks between tasks
+    async fn task_loop(&self, character: Arc<Character>, mut tasks: watch::Receiver<Tasks>) {
+        let name = character.name().to_string();
+
+        // the sender is dropped when the character is stopped
+        while tasks.has_changed().is_ok() {
+            let current = tasks.borrow_and_update().clone();
+
+            if current.is_empty() {
+                tracing::warn!(character = %name, "no tasks configured");
+                self.update(&name, |health| health.status = Status::Idle);
+
+                if tasks.changed().await.is_err() {
+                    return;
+                }
+
+                self.update(&name, |health| health.status = Status::Running);
+                continue;
+            }
+
+            let pass = {
+                let character = character.clone();
+                let mut changes = tasks.clone();
+
+                tokio::spawn(async move { run_tasks(&character, &current, &mut changes).await })
+            };
+
+            let incident = match pass.await {
+                Ok(Ok(true)) => {
+                    self.update(&name, |health| health.consecutive_failures = 0);
+                    continue;
+                }
+                Ok(Ok(false)) => {
+                    tokio::time::sleep(IDLE_DELAY).await;
+                    continue;
+                }
+                Ok(Err((task, e))) => Incident {
+                    at: Utc::now(),
+                    kind: IncidentKind::Error,
+                    task: Some(task),
+                    message: e.to_string(),
+                },
+                Err(e) => Incident {
+                    at: Utc::now(),
+                    kind: IncidentKind::Panic,
+                    task: None,
+                    message: panic_message(e),
+                },
+            };
+
+            self.recover(&name, incident).await;
+        }
+    }
+
+    async fn execution_loop(&self, character: Arc<Character>) {
+        let name = character.name().to_string();
+
+        loop {
+            let character = character.clone();
+            let result = {
+                let execution = tokio::spawn(async move { character.execution_loop().await });
+                // stop the character's execution loop along with this one
+                let _abort = AbortOnDrop(execution.abort_handle());
+
+                execution.await
+            };
+
+            let (kind, message) = match result {
+                Ok(Ok(())) => (IncidentKind::Error, "execution loop stopped".to_string()),
+                Ok(Err(e)) => (IncidentKind::Error, e.to_string()),
+                Err(e) => (IncidentKind::Panic, panic_message(e)),
+            };
+
+            self.recover(
+                &name,
+                Incident {
+                    at: Utc::now(),
+                    kind,
+                    task: None,
+                    message,
+                },
+            )
+            .await;
+        }
+    }
+
+    /// Record an incident and wait out the backoff before the loop is restarted
+    async fn recover(&self, name: &str, incident: Incident) {
+        let mut failures = 0;
+        let mut restarts = 0;
+        self.update(name, |health| {
+            health.consecutive_failures += 1;
+            health.restarts += 1;
+            health.status = Status::Restarting;
+            health.last_incident = Some(incident.clone());
+
+            failures = health.consecutive_failures;
+            restarts = health.restarts;
+        });
+
+        let backoff = self.policy.backoff(failures);
+
+        tracing::error!(
+            character = %name,
+            kind = ?incident.kind,
+            task = incident.task.as_deref().unwrap_or("-"),
+            error = %incident.message,
+            consecutive_failures = failures,
+            restarts,
+            backoff_secs = backoff.as_secs_f64(),
+            "character loop failed, restarting"
+        );
+
+        tokio::time::sleep(backoff).await;
+
+        self.update(name, |health| health.status = Status::Running);
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut CharacterHealth)) {
+        let mut health = self.health.write().expect("health lock poisoned");
+
+        f(health.entry(name.to_string()).or_default());
+    }
+}
+
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run every task once, in order, skipping those whose condition doesn't hold and stopping
+/// early if the tasks are replaced or the character is stopped. Returns whether any task
+/// executed an action, or the failed task and its error.
+async fn run_tasks(
+    character: &Character,
+    tasks: &[TaskConfig],
+    changes: &mut watch::Receiver<Tasks>,
+) -> Result<bool, (String, Error)> {
+    let mut ran = false;
+
+    for task_config in tasks {
+        if !matches!(changes.has_changed(), Ok(false)) {
+            break;
+        }
+
+        if let Some(condition) = &task_config.condition {
+            // skip the task if its condition doesn't hold
+            if !character.read_state(|state| conditions::evaluate(condition, state)) {
+                continue;
+            }
+        }
+
+        let executed = character.executed_actions();
+
+        tasks::run(character, &task_config.task)
+            .await
+            .map_err(|e| (format!("{:?}", task_config.task), e))?;
+
+        // a task that stopped before anything succeeded, e.g. on a full inventory, didn't run
+        ran |= character.executed_actions() > executed;
+    }
+
+    Ok(ran)
+}
+
+fn panic_message(e: JoinError) -> String {
+    if !e.is_panic() {
+        return e.to_string();
+    }
+
+    match e.into_panic().downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast::<&str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "unknown panic".into(),
+        },
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    ge::MAX_TRANSACTION_QUANTITY,
+    Character,
+};
+
+use super::go_to_nearest;
+
+/// Buy an item on the Grand Exchange until there are `quantity` of it in the inventory, as
+/// long as it costs at most `max_price` each and there is gold for it
+pub async fn buy(
+    character: &Character,
+    task: &Task,
+    code: &str,
+    max_price: i64,
+    quantity: i64,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    let mut at_exchange = false;
+
+    loop {
+        let missing = quantity - character.inventory_count(code);
+        if missing <= 0 {
+            return Ok(());
+        }
+
+        // prices change with every trade, so check before each one
+        let listing = character.grand_exchange().listing(code).await?;
+        let price = match listing.buy_price {
+            Some(price) if price > 0 && price <= max_price => price,
+            price => {
+                tracing::debug!(?price, "{code} costs more than {max_price}");
+                return Ok(());
+            }
+        };
+
+        let affordable = character.read_state(|state| state.gold) / price;
+        let quantity = missing
+            .min(MAX_TRANSACTION_QUANTITY)
+            .min(listing.stock)
+            .min(affordable);
+        if quantity <= 0 {
+            tracing::debug!("Can't buy any {code} at {price}");
+            return Ok(());
+        }
+
+        if !at_exchange {
+            go_to_nearest(character, task, position, "grand_exchange").await?;
+            at_exchange = true;
+        }
+
+        if let Err(e) = character.ge_buy(task, code, quantity, price).await?.await {
+            tracing::debug!(error = %e, "Failed to buy {quantity} {code} at {price}");
+            return Ok(());
+        }
+    }
+}
@@ -1,11 +1,8 @@
-use crate::{config::TaskName::CopperIngots, error::Error, Character};
+use crate::{config::Task::CopperIngots, error::Error, Character};
 
-pub async fn copper_ingots(character: &Character) -> Result<(), Error> {
-    // Move to forest forge
-    character.move_to(CopperIngots, 1, 5).await?;
-
-    // Craft copper ingot
-    character.crafting(CopperIngots, "copper").await?;
+use super::craft;
 
-    Ok(())
+pub async fn copper_ingots(character: &Character) -> Result<(), Error> {
+    // Craft a copper ingot at the nearest forge
+    craft(character, &CopperIngots, "copper", 1, None).await
 }
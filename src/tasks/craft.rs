@@ -0,0 +1,102 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    response::{Craft, Item},
+    Character,
+};
+
+use super::{go_to, go_to_nearest};
+
+pub async fn craft(
+    character: &Character,
+    task: &Task,
+    item: &str,
+    quantity: i64,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    let recipe = match character.game().item(item).await? {
+        Some(Item {
+            craft: Some(craft), ..
+        }) => craft,
+        _ => return Err(Error::NotCraftable(item.into())),
+    };
+
+    withdraw_materials(character, task, recipe, quantity).await?;
+
+    let position = match position {
+        Some(position) => position,
+        // workshops are named after the skill used to craft there
+        None => character.locate(&recipe.skill).await?,
+    };
+
+    go_to(character, task, position).await?;
+
+    match character.crafting(task, item, quantity).await?.await {
+        Ok(_) => Ok(()),
+        Err(Error::MissingItem) => {
+            tracing::debug!("Missing materials to craft {quantity} {item}");
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Withdraw the materials for `quantity` crafts that aren't in the inventory from the bank,
+/// as far as the bank has them
+async fn withdraw_materials(
+    character: &Character,
+    task: &Task,
+    recipe: &Craft,
+    quantity: i64,
+) -> Result<(), Error> {
+    let missing: Vec<(&str, i64)> = recipe
+        .items
+        .iter()
+        .map(|material| {
+            let needed = material.quantity * quantity;
+
+            (
+                material.code.as_str(),
+                needed - character.inventory_count(&material.code),
+            )
+        })
+        .filter(|(_, missing)| *missing > 0)
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let bank = character.bank().items().await?;
+    let withdrawals: Vec<(&str, i64)> = missing
+        .into_iter()
+        .map(|(code, missing)| {
+            (
+                code,
+                missing.min(bank.get(code).copied().unwrap_or_default()),
+            )
+        })
+        .filter(|(_, quantity)| *quantity > 0)
+        .collect();
+
+    if withdrawals.is_empty() {
+        return Ok(());
+    }
+
+    go_to_nearest(character, task, None, "bank").await?;
+
+    for (code, quantity) in withdrawals {
+        tracing::debug!("Withdrawing {quantity} {code} to craft with");
+
+        match character.withdraw(task, code, quantity).await?.await {
+            Ok(_) => {}
+            // the bank changed since it was looked up, craft with what there is
+            Err(Error::MissingItem) => {
+                tracing::debug!("Failed to withdraw {quantity} {code}");
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
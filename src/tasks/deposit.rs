@@ -0,0 +1,37 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    Character,
+};
+
+use super::go_to_nearest;
+
+/// Deposit `quantity` of an item in the bank, or all of it if `quantity` is `None`
+pub async fn deposit(
+    character: &Character,
+    task: &Task,
+    code: &str,
+    quantity: Option<i64>,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    go_to_nearest(character, task, position, "bank").await?;
+
+    let quantity = match quantity {
+        Some(quantity) => quantity,
+        None => character.inventory_count(code),
+    };
+
+    if quantity > 0 {
+        tracing::debug!("Depositing {} {}", quantity, code);
+
+        match character.deposit(task, code, quantity).await?.await {
+            Ok(_) => {}
+            Err(Error::MissingItem) => {
+                tracing::debug!("Not enough {code} in the inventory to deposit {quantity}");
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
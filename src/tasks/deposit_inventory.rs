@@ -1,18 +1,35 @@
-use crate::{config::TaskName::DepositInventory, error::Error, Character};
+use crate::{config::Task::DepositInventory, error::Error, Character};
 
+use super::go_to_nearest;
+
+/// Deposit the inventory and gold in the bank as allowed by the character's deposit policy,
+/// waiting for every deposit and stopping at the first that fails
 pub async fn deposit_inventory(character: &Character) -> Result<(), Error> {
-    character.move_to(DepositInventory, 4, 1).await?; // walk to the bank
+    go_to_nearest(character, &DepositInventory, None, "bank").await?; // walk to the bank
+
+    let policy = character.deposit_policy();
+    let inventory = character.inventory();
 
-    let (_, inventory) = character.inventory(DepositInventory).await?;
+    for slot in inventory.items() {
+        let quantity = policy.items_to_deposit(&slot.code, slot.quantity);
 
-    for slot in inventory {
-        if slot.quantity > 0 {
-            tracing::debug!("Depositing {} {}", slot.quantity, slot.code);
+        if quantity > 0 {
+            tracing::debug!("Depositing {} {}", quantity, slot.code);
             character
-                .deposit(DepositInventory, &slot.code, slot.quantity)
+                .deposit(&DepositInventory, &slot.code, quantity)
+                .await?
                 .await?;
         }
     }
 
+    let gold = policy.gold_to_deposit(character.read_state(|state| state.gold));
+    if gold > 0 {
+        tracing::debug!("Depositing {} gold", gold);
+        character
+            .deposit_gold(&DepositInventory, gold)
+            .await?
+            .await?;
+    }
+
     Ok(())
 }
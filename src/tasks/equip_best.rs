@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::{Activity, Task},
+    equipment,
+    error::Error,
+    Character,
+};
+
+use super::withdraw;
+
+/// Swap in the best gear for `activity` from the inventory and bank, see
+/// [`equipment::best_changes`]
+pub async fn equip_best(
+    character: &Character,
+    task: &Task,
+    activity: &Activity,
+) -> Result<(), Error> {
+    let game = character.game();
+
+    let mut inventory: HashMap<String, i64> = HashMap::new();
+    for slot in character.inventory().items() {
+        *inventory.entry(slot.code.clone()).or_default() += slot.quantity;
+    }
+
+    let bank = character.bank().items().await?;
+    let items = game.items().await?;
+    let monsters = game.monsters().await?;
+
+    let changes = character.read_state(|state| {
+        equipment::best_changes(state, items, &inventory, &bank, activity, monsters)
+    })?;
+
+    for change in changes.iter().filter(|change| change.from_bank) {
+        withdraw(character, task, &change.code, 1, None).await?;
+    }
+
+    for change in changes {
+        let slot = change.slot.code();
+
+        if !character.read_state(|state| change.slot.equipped(state).is_empty()) {
+            if let Err(e) = character.unequip(task, slot).await?.await {
+                tracing::warn!(error = %e, "Failed to unequip the {slot}");
+                continue;
+            }
+        }
+
+        tracing::info!("Equipping {} as {slot}", change.code);
+        if let Err(e) = character.equip(task, &change.code, slot).await?.await {
+            tracing::warn!(error = %e, "Failed to equip {}", change.code);
+        }
+    }
+
+    Ok(())
+}
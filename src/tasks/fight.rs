@@ -0,0 +1,106 @@
+use crate::{
+    combat,
+    config::{Position, Task},
+    error::Error,
+    response::{ActionData, Fight, FightResult},
+    Character,
+};
+
+use super::{go_to_nearest, heal};
+
+pub async fn fight(
+    character: &Character,
+    task: &Task,
+    monster: &str,
+    times: u32,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    match fight_winnable(character, task, monster, times, position).await {
+        // not a failure, the refusal has been logged and there is nothing else to do
+        Err(Error::Unwinnable(_)) => Ok(()),
+        result => result,
+    }
+}
+
+/// Like `fight`, but fails with `Error::Unwinnable` instead of skipping a fight the character
+/// is predicted to lose even at full hp
+pub(crate) async fn fight_winnable(
+    character: &Character,
+    task: &Task,
+    monster: &str,
+    times: u32,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    heal(character, task).await?;
+    ensure_winnable(character, task, monster).await?;
+    go_to_nearest(character, task, position, monster).await?;
+
+    for _ in 0..times {
+        heal(character, task).await?;
+        ensure_winnable(character, task, monster).await?;
+
+        match character.fight(task).await?.await {
+            // the character respawns elsewhere after losing, so stop here
+            Ok(ActionData {
+                fight:
+                    Some(Fight {
+                        result: FightResult::Lose,
+                        ..
+                    }),
+                ..
+            }) => {
+                tracing::warn!("Lost the fight against {monster}");
+                break;
+            }
+            Ok(_) => {}
+            Err(Error::InventoryFull) => {
+                tracing::debug!("Inventory is full");
+                break;
+            }
+            Err(Error::NotFoundOnMap) => {
+                // the monster has moved, look it up again next time
+                character.game().map.invalidate().await;
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Refuse to fight a monster the character is predicted to lose against, resting to full hp
+/// first if that might change the outcome
+pub(crate) async fn ensure_winnable(
+    character: &Character,
+    task: &Task,
+    monster: &str,
+) -> Result<(), Error> {
+    let monsters = character.game().monsters().await?;
+    let stats = match monsters.iter().find(|m| m.code == monster) {
+        Some(stats) => stats,
+        None => return Ok(()),
+    };
+
+    let predict = || character.read_state(|state| combat::predict(state, stats));
+    let mut prediction = predict();
+
+    if !prediction.is_win() && character.read_state(|state| state.hp < state.max_hp) {
+        tracing::debug!("Resting before fighting {monster}");
+        character.rest(task).await?.await?;
+        prediction = predict();
+    }
+
+    if prediction.is_win() {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        monster,
+        win_probability = prediction.win_probability,
+        hp_loss = prediction.hp_loss,
+        "Refusing a fight predicted to be lost"
+    );
+
+    Err(Error::Unwinnable(monster.into()))
+}
@@ -0,0 +1,36 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    Character,
+};
+
+use super::go_to_nearest;
+
+pub async fn gather(
+    character: &Character,
+    task: &Task,
+    resource: &str,
+    times: u32,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    go_to_nearest(character, task, position, resource).await?;
+
+    // Gather, stopping early once the inventory is full
+    for _ in 0..times {
+        match character.gathering(task).await?.await {
+            Ok(_) => {}
+            Err(Error::InventoryFull) => {
+                tracing::debug!("Inventory is full");
+                break;
+            }
+            Err(Error::NotFoundOnMap) => {
+                // the resource has moved, look it up again next time
+                character.game().map.invalidate().await;
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(())
+}
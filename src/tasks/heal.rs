@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use crate::{config::Task, error::Error, inventory::Inventory, response::Item, Character};
+
+/// Heal according to the character's heal policy if its hp is low: eat the food in the
+/// inventory that heals the most, then rest if that wasn't enough
+pub async fn heal(character: &Character, task: &Task) -> Result<(), Error> {
+    let policy = character.heal_policy();
+    let low = || character.read_state(|state| policy.should_heal(state.hp, state.max_hp));
+
+    if !low() {
+        return Ok(());
+    }
+
+    if policy.eat_food {
+        let items = character.game().items().await?;
+        let (level, missing) = character.read_state(|state| (state.level, state.max_hp - state.hp));
+
+        let food = best_food(&character.inventory(), items, level);
+
+        if let Some((code, quantity, heal)) = food {
+            // just enough to heal fully, or as much as there is
+            let quantity = ((missing + heal - 1) / heal).min(quantity);
+
+            tracing::debug!("Eating {quantity} {code}");
+            if let Err(e) = character.use_item(task, &code, quantity).await?.await {
+                tracing::warn!(error = %e, "Failed to eat {code}");
+            }
+        }
+    }
+
+    if low() {
+        tracing::debug!("Resting");
+        character.rest(task).await?.await?;
+    }
+
+    Ok(())
+}
+
+/// The food in the inventory that heals the most, with its quantity and how much it heals
+fn best_food(
+    inventory: &Inventory,
+    items: &HashMap<String, Item>,
+    level: i64,
+) -> Option<(String, i64, i64)> {
+    inventory
+        .items()
+        .filter_map(|slot| {
+            let item = items.get(&slot.code)?;
+            let heal = item.effects.iter().find(|effect| effect.name == "heal")?;
+
+            (item.kind == "consumable" && item.level <= level && heal.value > 0).then_some((
+                slot.code.clone(),
+                slot.quantity,
+                heal.value,
+            ))
+        })
+        .max_by_key(|(_, _, heal)| *heal)
+}
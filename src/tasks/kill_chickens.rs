@@ -1,11 +1,8 @@
-use crate::{config::TaskName::KillChickens, error::Error, Character};
+use crate::{config::Task::KillChickens, error::Error, Character};
 
-pub async fn kill_chickens(character: &Character) -> Result<(), Error> {
-    // Move to the chickens
-    character.move_to(KillChickens, 0, 1).await?;
-
-    // Attack the chickens
-    character.fight(KillChickens).await?;
+use super::fight;
 
-    Ok(())
+pub async fn kill_chickens(character: &Character) -> Result<(), Error> {
+    // Fight the nearest chicken
+    fight(character, &KillChickens, "chicken", 1, None).await
 }
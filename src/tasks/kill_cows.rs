@@ -1,11 +1,8 @@
-use crate::{config::TaskName::KillCows, error::Error, Character};
+use crate::{config::Task::KillCows, error::Error, Character};
 
-pub async fn kill_cows(character: &Character) -> Result<(), Error> {
-    // Move to the cows
-    character.move_to(KillCows, 0, 2).await?;
-
-    // Attack the cows
-    character.fight(KillCows).await?;
+use super::fight;
 
-    Ok(())
+pub async fn kill_cows(character: &Character) -> Result<(), Error> {
+    // Fight the nearest cow
+    fight(character, &KillCows, "cow", 1, None).await
 }
@@ -1,11 +1,8 @@
-use crate::{config::TaskName::KillYellowSlime, error::Error, Character};
+use crate::{config::Task::KillYellowSlime, error::Error, Character};
 
-pub async fn kill_yellow_slime(character: &Character) -> Result<(), Error> {
-    // Move to the slime
-    character.move_to(KillYellowSlime, 4, -1).await?;
-
-    // Attack the slime
-    character.fight(KillYellowSlime).await?;
+use super::fight;
 
-    Ok(())
+pub async fn kill_yellow_slime(character: &Character) -> Result<(), Error> {
+    // Fight the nearest yellow slime
+    fight(character, &KillYellowSlime, "yellow_slime", 1, None).await
 }
@@ -1,13 +1,8 @@
-use crate::{config::TaskName::MineCopper, error::Error, Character};
+use crate::{config::Task::MineCopper, error::Error, Character};
 
-pub async fn mine_copper(character: &Character) -> Result<(), Error> {
-    // Move to the copper
-    character.move_to(MineCopper, 2, 0).await?;
-
-    // Gather 6 copper
-    for _ in 0..6 {
-        character.gathering(MineCopper).await?;
-    }
+use super::gather;
 
-    Ok(())
+pub async fn mine_copper(character: &Character) -> Result<(), Error> {
+    // Gather 6 copper at the nearest copper rocks
+    gather(character, &MineCopper, "copper_rocks", 6, None).await
 }
@@ -1,15 +1,123 @@
+mod buy;
 mod copper_ingots;
+mod craft;
+mod deposit;
 mod deposit_inventory;
+mod equip_best;
+mod fight;
+mod gather;
+mod heal;
 mod kill_chickens;
 mod kill_cows;
 mod kill_yellow_slime;
 mod mine_copper;
 mod mine_iron;
+mod move_to;
+mod produce;
+mod sell;
+mod task_master;
+mod withdraw;
 
+pub use buy::*;
 pub use copper_ingots::*;
+pub use craft::*;
+pub use deposit::*;
 pub use deposit_inventory::*;
+pub use equip_best::*;
+pub use fight::*;
+pub use gather::*;
+pub use heal::*;
 pub use kill_chickens::*;
 pub use kill_cows::*;
 pub use kill_yellow_slime::*;
 pub use mine_copper::*;
 pub use mine_iron::*;
+pub use move_to::*;
+pub use produce::*;
+pub use sell::*;
+pub use task_master::*;
+pub use withdraw::*;
+
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    Character,
+};
+
+/// Run a single iteration of a task
+pub async fn run(character: &Character, task: &Task) -> Result<(), Error> {
+    match task {
+        Task::Gather {
+            resource,
+            times,
+            position,
+        } => gather(character, task, resource, *times, *position).await,
+        Task::Fight {
+            monster,
+            times,
+            position,
+        } => fight(character, task, monster, *times, *position).await,
+        Task::Craft {
+            item,
+            quantity,
+            position,
+        } => craft(character, task, item, *quantity, *position).await,
+        Task::Produce { item, quantity } => produce(character, task, item, *quantity).await,
+        Task::Move { x, y } => move_to(character, task, *x, *y).await,
+        Task::Deposit {
+            code,
+            quantity,
+            position,
+        } => deposit(character, task, code, *quantity, *position).await,
+        Task::Withdraw {
+            code,
+            quantity,
+            position,
+        } => withdraw(character, task, code, *quantity, *position).await,
+        Task::Sell {
+            code,
+            min_price,
+            keep,
+            position,
+        } => sell(character, task, code, *min_price, *keep, *position).await,
+        Task::Buy {
+            code,
+            max_price,
+            quantity,
+            position,
+        } => buy(character, task, code, *max_price, *quantity, *position).await,
+        Task::EquipBest { activity } => equip_best(character, task, activity).await,
+        Task::TaskMaster { task_type } => task_master(character, task, *task_type).await,
+        Task::MineCopper => mine_copper(character).await,
+        Task::MineIron => mine_iron(character).await,
+        Task::CopperIngots => copper_ingots(character).await,
+        Task::KillChickens => kill_chickens(character).await,
+        Task::DepositInventory => deposit_inventory(character).await,
+        Task::KillYellowSlime => kill_yellow_slime(character).await,
+        Task::KillCows => kill_cows(character).await,
+    }
+}
+
+/// Move to `position`, waiting for the move so the character state is up to date
+async fn go_to(character: &Character, task: &Task, position: Position) -> Result<(), Error> {
+    match character.move_to(task, position.x, position.y).await?.await {
+        // already being there is fine
+        Ok(_) | Err(Error::AlreadyAtDestination) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Move to `position`, or the nearest tile with content `code` if no position is given
+async fn go_to_nearest(
+    character: &Character,
+    task: &Task,
+    position: Option<Position>,
+    code: &str,
+) -> Result<(), Error> {
+    let position = match position {
+        Some(position) => position,
+        None => character.locate(code).await?,
+    };
+
+    go_to(character, task, position).await
+}
@@ -0,0 +1,11 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    Character,
+};
+
+use super::go_to;
+
+pub async fn move_to(character: &Character, task: &Task, x: i32, y: i32) -> Result<(), Error> {
+    go_to(character, task, Position { x, y }).await
+}
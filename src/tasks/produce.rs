@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::{
+    config::Task,
+    error::Error,
+    recipe::{self, intake, Planner, Step},
+    response::{ActionData, Fight, FightResult},
+    Character,
+};
+
+use super::{craft, ensure_winnable, go_to_nearest, heal, withdraw};
+
+/// Obtain `quantity` of `item` by following the plan from its crafting tree. The plan is
+/// made again on every run, so a run that stops early picks up where it left off.
+pub async fn produce(
+    character: &Character,
+    task: &Task,
+    item: &str,
+    quantity: i64,
+) -> Result<(), Error> {
+    let bank = character.bank().items().await?;
+
+    match follow_plan(character, task, item, quantity, bank).await {
+        // not a failure, the refusal has been logged like for the `Fight` task
+        Err(Error::Unwinnable(_)) => Ok(()),
+        result => result,
+    }
+}
+
+/// Obtain `quantity` more of `item` without withdrawing any from the bank, for when only
+/// newly gathered or crafted items count. Fails with `Error::Unwinnable` if it takes a fight
+/// the character is predicted to lose.
+pub async fn produce_more(
+    character: &Character,
+    task: &Task,
+    item: &str,
+    quantity: i64,
+) -> Result<(), Error> {
+    let mut bank = character.bank().items().await?;
+    bank.remove(item);
+
+    let target = character.inventory_count(item) + quantity;
+    follow_plan(character, task, item, target, bank).await
+}
+
+/// Follow plans for `quantity` of `item` in batches that fit in the free inventory space,
+/// crafting what each batch makes before gathering for the next
+async fn follow_plan(
+    character: &Character,
+    task: &Task,
+    item: &str,
+    quantity: i64,
+    mut bank: HashMap<String, i64>,
+) -> Result<(), Error> {
+    let game = character.game();
+    let (items, resources, monsters) = (
+        game.items().await?,
+        game.resources().await?,
+        game.monsters().await?,
+    );
+
+    loop {
+        let held = character.inventory_count(item);
+        if held >= quantity {
+            return Ok(());
+        }
+
+        let inventory = character.inventory();
+        let free = inventory.max_items() - inventory.total_items();
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        for slot in inventory.items() {
+            *counts.entry(slot.code.clone()).or_default() += slot.quantity;
+        }
+
+        let plan = |target: i64| {
+            Planner::new(items, resources, monsters, counts.clone(), bank.clone())
+                .plan(item, target)
+        };
+
+        // the largest batch whose materials fit, a batch needs at least as much as a smaller one
+        let (mut fits, mut too_many) = (held, quantity + 1);
+        while too_many - fits > 1 {
+            let target = (fits + too_many) / 2;
+            if intake(&plan(target)?) <= free {
+                fits = target;
+            } else {
+                too_many = target;
+            }
+        }
+
+        if fits == held {
+            tracing::warn!("Not enough room in the inventory to produce {item}");
+            return Ok(());
+        }
+
+        let batch = plan(fits)?;
+        tracing::debug!(?batch, "Producing {} {item}", fits - held);
+
+        for step in &batch {
+            if let Step::Withdraw { code, quantity } = step {
+                recipe::take(&mut bank, code, *quantity);
+            }
+        }
+
+        if !follow_steps(character, task, batch).await? {
+            return Ok(());
+        }
+
+        if character.inventory_count(item) <= held {
+            tracing::debug!("Producing {item} made no progress");
+            return Ok(());
+        }
+    }
+}
+
+/// Follow the steps of a plan, returning whether all of them were followed or they stopped
+/// early, e.g. on a full inventory
+async fn follow_steps(character: &Character, task: &Task, steps: Vec<Step>) -> Result<bool, Error> {
+    for step in steps {
+        match step {
+            Step::Withdraw { code, quantity } => {
+                withdraw(character, task, &code, quantity, None).await?
+            }
+            Step::Gather {
+                resource,
+                code,
+                quantity,
+            } => {
+                let target = character.inventory_count(&code) + quantity;
+                go_to_nearest(character, task, None, &resource).await?;
+
+                while character.inventory_count(&code) < target {
+                    match character.gathering(task).await?.await {
+                        Ok(_) => {}
+                        Err(Error::InventoryFull) => {
+                            tracing::debug!("Stopped gathering {code}, the inventory is full");
+                            return Ok(false);
+                        }
+                        Err(Error::NotFoundOnMap) => {
+                            // the resource has moved, look it up again next time
+                            character.game().map.invalidate().await;
+                            return Ok(false);
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Step::Fight {
+                monster,
+                code,
+                quantity,
+            } => {
+                let target = character.inventory_count(&code) + quantity;
+                heal(character, task).await?;
+                ensure_winnable(character, task, &monster).await?;
+                go_to_nearest(character, task, None, &monster).await?;
+
+                while character.inventory_count(&code) < target {
+                    heal(character, task).await?;
+                    ensure_winnable(character, task, &monster).await?;
+
+                    match character.fight(task).await?.await {
+                        Ok(ActionData {
+                            fight:
+                                Some(Fight {
+                                    result: FightResult::Lose,
+                                    ..
+                                }),
+                            ..
+                        }) => {
+                            tracing::warn!("Lost the fight against {monster}");
+                            return Ok(false);
+                        }
+                        Ok(_) => {}
+                        Err(Error::InventoryFull) => {
+                            tracing::debug!("Stopped fighting for {code}, the inventory is full");
+                            return Ok(false);
+                        }
+                        Err(Error::NotFoundOnMap) => {
+                            // the monster has moved, look it up again next time
+                            character.game().map.invalidate().await;
+                            return Ok(false);
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            Step::Craft { code, quantity } => craft(character, task, &code, quantity, None).await?,
+        }
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,48 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    ge::MAX_TRANSACTION_QUANTITY,
+    Character,
+};
+
+use super::go_to_nearest;
+
+/// Sell all but `keep` of an item on the Grand Exchange while it sells for at least
+/// `min_price` each
+pub async fn sell(
+    character: &Character,
+    task: &Task,
+    code: &str,
+    min_price: i64,
+    keep: i64,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    let mut at_exchange = false;
+
+    loop {
+        let surplus = character.inventory_count(code) - keep;
+        if surplus <= 0 {
+            return Ok(());
+        }
+
+        // prices change with every trade, so check before each one
+        let price = match character.grand_exchange().listing(code).await?.sell_price {
+            Some(price) if price >= min_price => price,
+            price => {
+                tracing::debug!(?price, "{code} sells for less than {min_price}");
+                return Ok(());
+            }
+        };
+
+        if !at_exchange {
+            go_to_nearest(character, task, position, "grand_exchange").await?;
+            at_exchange = true;
+        }
+
+        let quantity = surplus.min(MAX_TRANSACTION_QUANTITY);
+        if let Err(e) = character.ge_sell(task, code, quantity, price).await?.await {
+            tracing::debug!(error = %e, "Failed to sell {quantity} {code} at {price}");
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    config::{Task, TaskType},
+    error::Error,
+    Character,
+};
+
+use super::{fight_winnable, go_to_nearest, produce_more};
+
+/// Work through a task from the tasks master of `task_type`: accept one if the character has
+/// none, fight the monsters or produce and trade the items it asks for, then turn it in for a
+/// tasks coin. Tasks against monsters the character is predicted to lose to are cancelled.
+pub async fn task_master(
+    character: &Character,
+    task: &Task,
+    task_type: TaskType,
+) -> Result<(), Error> {
+    if character.read_state(|state| state.task.is_empty()) {
+        go_to_nearest(character, task, None, task_type.code()).await?;
+
+        let accepted = character.task_new(task).await?.await?;
+        if let Some(assignment) = accepted.task {
+            tracing::info!(
+                "Accepted a task for {} {} ({})",
+                assignment.total,
+                assignment.code,
+                assignment.kind
+            );
+        }
+    }
+
+    let (code, kind, remaining) = character.read_state(|state| {
+        (
+            state.task.clone(),
+            state.task_type.clone(),
+            state.task_total - state.task_progress,
+        )
+    });
+
+    if remaining > 0 {
+        let worked = match kind.as_str() {
+            "monsters" => fight_winnable(character, task, &code, remaining as u32, None).await,
+            _ => trade_items(character, task, &code).await,
+        };
+
+        match worked {
+            Err(Error::Unwinnable(_)) => return cancel(character, task, &kind).await,
+            Err(e) => return Err(e),
+            Ok(()) => {}
+        }
+    }
+
+    if character.read_state(|state| state.task_total > 0 && state.task_progress >= state.task_total)
+    {
+        go_to_nearest(character, task, None, &kind).await?;
+
+        let completed = character.task_complete(task).await?.await?;
+        if let Some(reward) = completed.reward {
+            tracing::info!("Completed the task for {} {}", reward.quantity, reward.code);
+        }
+    }
+
+    Ok(())
+}
+
+/// Produce the items of an items task and trade them at the tasks master until the task is
+/// complete, or until producing stops early, e.g. on a full inventory
+async fn trade_items(character: &Character, task: &Task, code: &str) -> Result<(), Error> {
+    loop {
+        let remaining = character.read_state(|state| state.task_total - state.task_progress);
+        if remaining <= 0 {
+            return Ok(());
+        }
+
+        let held = character.inventory_count(code);
+        if held < remaining {
+            produce_more(character, task, code, remaining - held).await?;
+        }
+
+        let quantity = character.inventory_count(code).min(remaining);
+        if quantity == 0 {
+            return Ok(());
+        }
+
+        go_to_nearest(character, task, None, "items").await?;
+        character.task_trade(task, code, quantity).await?.await?;
+    }
+}
+
+/// Abandon the current task at the tasks master that gave it
+async fn cancel(character: &Character, task: &Task, kind: &str) -> Result<(), Error> {
+    tracing::warn!("Cancelling a task that can't be completed");
+
+    go_to_nearest(character, task, None, kind).await?;
+    character.task_cancel(task).await?.await?;
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::{
+    config::{Position, Task},
+    error::Error,
+    Character,
+};
+
+use super::go_to_nearest;
+
+pub async fn withdraw(
+    character: &Character,
+    task: &Task,
+    code: &str,
+    quantity: i64,
+    position: Option<Position>,
+) -> Result<(), Error> {
+    go_to_nearest(character, task, position, "bank").await?;
+
+    match character.withdraw(task, code, quantity).await?.await {
+        Ok(_) => Ok(()),
+        Err(Error::MissingItem) => {
+            tracing::debug!("Not enough {code} in the bank to withdraw {quantity}");
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,298 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::Range,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    config::{Activity, Condition, Config, Position, Task},
+    error::Error,
+    game_data::GameData,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem with the config, at a 1-based line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, self.severity, self.message
+        )
+    }
+}
+
+// Where the characters and the fields of their tasks are in the source
+#[derive(Deserialize)]
+struct Spans {
+    #[serde(default)]
+    characters: Vec<Spanned<CharacterSpans>>,
+}
+
+#[derive(Deserialize)]
+struct CharacterSpans {
+    name: Spanned<String>,
+    #[serde(default)]
+    tasks: Vec<Spanned<HashMap<String, Spanned<toml::Value>>>>,
+}
+
+/// Parse `source` as a config and check it against the game data: item, monster and resource
+/// codes, positions on the map and conditions that never hold. Diagnostics are sorted by
+/// line, and there is at least one error if the config can't be used.
+pub async fn validate(source: &str, game: &GameData) -> Result<Vec<Diagnostic>, Error> {
+    let config: Config = match toml::from_str(source) {
+        Ok(config) => config,
+        Err(e) => {
+            let span = e.span().unwrap_or(0..0);
+            return Ok(vec![diagnostic(
+                source,
+                span,
+                Severity::Error,
+                e.message().to_string(),
+            )]);
+        }
+    };
+
+    let spans: Spans = toml::from_str(source).expect("config parsed, so its spans do");
+
+    let checker = Checker {
+        items: game.items().await?.keys().map(String::as_str).collect(),
+        craftable: game
+            .items()
+            .await?
+            .values()
+            .filter(|item| item.craft.is_some())
+            .map(|item| item.code.as_str())
+            .collect(),
+        resources: game
+            .resources()
+            .await?
+            .iter()
+            .map(|resource| resource.code.as_str())
+            .collect(),
+        monsters: game
+            .monsters()
+            .await?
+            .iter()
+            .map(|monster| monster.code.as_str())
+            .collect(),
+        tiles: game
+            .map
+            .tiles()
+            .await?
+            .iter()
+            .map(|tile| (tile.x, tile.y))
+            .collect(),
+    };
+
+    let mut diagnostics = Vec::new();
+    let mut names = HashSet::new();
+
+    for (character, character_spans) in config.characters.iter().zip(&spans.characters) {
+        if !names.insert(&character.name) {
+            diagnostics.push(diagnostic(
+                source,
+                character_spans.get_ref().name.span(),
+                Severity::Error,
+                format!("character {} is configured more than once", character.name),
+            ));
+        }
+
+        for (task, task_spans) in character.tasks.iter().zip(&character_spans.get_ref().tasks) {
+            // point at the field a problem is with, or the whole task
+            let span = |field: &str| match task_spans.get_ref().get(field) {
+                Some(value) => value.span(),
+                None => task_spans.span(),
+            };
+
+            for (field, severity, message) in checker.task(&task.task) {
+                diagnostics.push(diagnostic(source, span(field), severity, message));
+            }
+
+            if let Some(condition) = &task.condition {
+                for (severity, message) in checker.condition(condition) {
+                    diagnostics.push(diagnostic(source, span("condition"), severity, message));
+                }
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| (d.line, d.column, d.severity));
+
+    Ok(diagnostics)
+}
+
+struct Checker<'a> {
+    items: HashSet<&'a str>,
+    craftable: HashSet<&'a str>,
+    resources: HashSet<&'a str>,
+    monsters: HashSet<&'a str>,
+    tiles: HashSet<(i64, i64)>,
+}
+
+impl Checker<'_> {
+    /// Problems with a task, with the field each is with
+    fn task(&self, task: &Task) -> Vec<(&'static str, Severity, String)> {
+        let mut problems = Vec::new();
+
+        let position = match task {
+            Task::Gather {
+                resource, position, ..
+            } => {
+                problems.extend(unknown(&self.resources, "resource", resource));
+                *position
+            }
+            Task::Fight {
+                monster, position, ..
+            } => {
+                problems.extend(unknown(&self.monsters, "monster", monster));
+                *position
+            }
+            Task::Craft { item, position, .. } => {
+                if self.items.contains(item.as_str()) && !self.craftable.contains(item.as_str()) {
+                    problems.push(("item", Severity::Error, format!("{item} can't be crafted")));
+                }
+                problems.extend(unknown(&self.items, "item", item));
+                *position
+            }
+            Task::Produce { item, .. } => {
+                problems.extend(unknown(&self.items, "item", item));
+                None
+            }
+            Task::Deposit { code, position, .. }
+            | Task::Withdraw { code, position, .. }
+            | Task::Sell { code, position, .. }
+            | Task::Buy { code, position, .. } => {
+                problems.extend(unknown(&self.items, "code", code));
+                *position
+            }
+            Task::EquipBest {
+                activity: Activity::Fight(monster),
+            } => {
+                problems.extend(unknown(&self.monsters, "activity", monster));
+                None
+            }
+            Task::Move { x, y } => Some(Position { x: *x, y: *y }),
+            _ => None,
+        };
+
+        if let Some(Position { x, y }) = position {
+            if !self.tiles.contains(&(x as i64, y as i64)) {
+                let field = match task {
+                    Task::Move { .. } => "x",
+                    _ => "position",
+                };
+                problems.push((field, Severity::Error, format!("({x}, {y}) is off the map")));
+            }
+        }
+
+        problems
+    }
+
+    /// Problems with a task's condition
+    fn condition(&self, condition: &Condition) -> Vec<(Severity, String)> {
+        let mut problems = Vec::new();
+        self.unknown_items(condition, &mut problems);
+
+        if never_holds(condition) {
+            problems.push((
+                Severity::Warning,
+                "condition never holds, so the task never runs".into(),
+            ));
+        }
+
+        problems
+    }
+
+    fn unknown_items(&self, condition: &Condition, problems: &mut Vec<(Severity, String)>) {
+        match condition {
+            Condition::All(conditions) | Condition::Any(conditions) => {
+                for condition in conditions {
+                    self.unknown_items(condition, problems);
+                }
+            }
+            Condition::Not(condition) => self.unknown_items(condition, problems),
+            Condition::ItemCountAtLeast { code, .. } if !self.items.contains(code.as_str()) => {
+                problems.push((Severity::Error, format!("unknown item {code}")));
+            }
+            _ => {}
+        }
+    }
+}
+
+// An error if `code` isn't one of the known codes of its kind, e.g. `resource`
+fn unknown(
+    known: &HashSet<&str>,
+    field: &'static str,
+    code: &str,
+) -> Option<(&'static str, Severity, String)> {
+    let kind = match field {
+        "resource" => "resource",
+        "monster" | "activity" => "monster",
+        _ => "item",
+    };
+
+    (!known.contains(code)).then(|| (field, Severity::Error, format!("unknown {kind} {code}")))
+}
+
+/// Whether a condition can't hold for any character state
+fn never_holds(condition: &Condition) -> bool {
+    match condition {
+        Condition::All(conditions) => conditions.iter().any(never_holds),
+        Condition::Any(conditions) => conditions.iter().all(never_holds),
+        Condition::Not(condition) => always_holds(condition),
+        Condition::HpBelow(hp) => *hp <= 0,
+        Condition::InventoryFreeSlotsBelow(n) => *n <= 0,
+        _ => false,
+    }
+}
+
+/// Whether a condition holds for every character state
+fn always_holds(condition: &Condition) -> bool {
+    match condition {
+        Condition::All(conditions) => conditions.iter().all(always_holds),
+        Condition::Any(conditions) => conditions.iter().any(always_holds),
+        Condition::Not(condition) => never_holds(condition),
+        Condition::ItemCountAtLeast { n, .. } => *n <= 0,
+        Condition::SkillLevelAtLeast { level, .. } => *level <= 1,
+        Condition::GoldAtLeast(gold) => *gold <= 0,
+        _ => false,
+    }
+}
+
+fn diagnostic(source: &str, span: Range<usize>, severity: Severity, message: String) -> Diagnostic {
+    let before = &source[..span.start.min(source.len())];
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+
+    Diagnostic {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+        severity,
+        message,
+    }
+}
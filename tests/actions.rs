@@ -0,0 +1,200 @@
+mod common;
+
+use artifacts::{
+    config::Task,
+    error::Error,
+    inventory::Inventory,
+    response::{BankUpdate, FightResult},
+};
+use chrono::{Duration, Utc};
+use common::{Server, NAME};
+
+const TASK: Task = Task::Move { x: 0, y: 0 };
+
+#[tokio::test]
+async fn move_updates_state() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let data = character.move_to(&TASK, 2, 0).await.unwrap().await.unwrap();
+    let destination = data.destination.unwrap();
+    assert_eq!((destination.x, destination.y), (2, 0));
+    assert_eq!(character.read_state(|c| (c.x, c.y)), (2, 0));
+
+    let again = character.move_to(&TASK, 2, 0).await.unwrap().await;
+    assert!(matches!(again, Err(Error::AlreadyAtDestination)));
+}
+
+#[tokio::test]
+async fn cooldown_is_retried() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    // the server thinks the character is still on cooldown, the action must be retried
+    server.world(|world| {
+        world.character_mut(NAME).unwrap().cooldown_expiration =
+            Some(Utc::now() + Duration::milliseconds(200));
+    });
+
+    let data = character.move_to(&TASK, 0, 1).await.unwrap().await.unwrap();
+    assert_eq!((data.character.x, data.character.y), (0, 1));
+}
+
+#[tokio::test]
+async fn gathering_fills_inventory() {
+    let server = Server::start().await;
+    server.world(|world| world.character_mut(NAME).unwrap().inventory = Inventory::new(20, 2));
+    let character = server.character().await;
+
+    let wrong_tile = character.gathering(&TASK).await.unwrap().await;
+    assert!(matches!(wrong_tile, Err(Error::NotFoundOnMap)));
+
+    character.move_to(&TASK, 2, 0).await.unwrap().await.unwrap();
+    for _ in 0..2 {
+        let data = character.gathering(&TASK).await.unwrap().await.unwrap();
+        assert_eq!(data.details.unwrap().items[0].code, "copper_ore");
+    }
+    assert_eq!(character.inventory_count("copper_ore"), 2);
+
+    let full = character.gathering(&TASK).await.unwrap().await;
+    assert!(matches!(full, Err(Error::InventoryFull)));
+}
+
+#[tokio::test]
+async fn craft_and_deposit() {
+    let server = Server::start().await;
+    server.world(|world| world.give(NAME, "copper_ore", 25));
+    let character = server.character().await;
+
+    character.move_to(&TASK, 1, 5).await.unwrap().await.unwrap();
+    character
+        .crafting(&TASK, "copper", 2)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(character.inventory_count("copper"), 2);
+    assert_eq!(character.inventory_count("copper_ore"), 5);
+
+    let missing = character.crafting(&TASK, "copper", 1).await.unwrap().await;
+    assert!(matches!(missing, Err(Error::MissingItem)));
+
+    character.move_to(&TASK, 4, 1).await.unwrap().await.unwrap();
+    let data = character
+        .deposit(&TASK, "copper", 2)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(character.inventory_count("copper"), 0);
+    assert!(matches!(
+        data.bank,
+        Some(BankUpdate::Items(items)) if items[0].code == "copper" && items[0].quantity == 2
+    ));
+}
+
+#[tokio::test]
+async fn fight_and_equip() {
+    let server = Server::start().await;
+    server.world(|world| world.give(NAME, "copper_helmet", 1));
+    let character = server.character().await;
+
+    character
+        .equip(&TASK, "copper_helmet", "helmet")
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(
+        character.read_state(|c| c.helmet_slot.clone()),
+        "copper_helmet"
+    );
+    assert_eq!(character.read_state(|c| c.res_water), 5);
+
+    character.move_to(&TASK, 0, 1).await.unwrap().await.unwrap();
+    let data = character.fight(&TASK).await.unwrap().await.unwrap();
+    assert_eq!(data.fight.unwrap().result, FightResult::Win);
+    assert!(character.read_state(|c| c.xp) > 0);
+}
+
+#[tokio::test]
+async fn bank_gold_and_expansion() {
+    let server = Server::start().await;
+    server.world(|world| world.character_mut(NAME).unwrap().gold = 5000);
+    let character = server.character().await;
+
+    character.move_to(&TASK, 4, 1).await.unwrap().await.unwrap();
+    character
+        .deposit_gold(&TASK, 1000)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    character
+        .withdraw_gold(&TASK, 400)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(character.read_state(|c| c.gold), 4400);
+
+    let details = character.bank().details().await.unwrap();
+    assert_eq!((details.gold, details.expansions), (600, 0));
+
+    let poor = character.buy_bank_expansion(&TASK).await.unwrap().await;
+    assert!(matches!(poor, Err(Error::APIError(e)) if e.code == 492));
+
+    character
+        .withdraw_gold(&TASK, 600)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    character
+        .buy_bank_expansion(&TASK)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(character.read_state(|c| c.gold), 500);
+
+    let details = character.bank().details().await.unwrap();
+    assert_eq!((details.gold, details.expansions), (0, 1));
+}
+
+#[tokio::test]
+async fn trade_progresses_items_task() {
+    let server = Server::start().await;
+    server.world(|world| {
+        let character = world.character_mut(NAME).unwrap();
+        character.task = "copper_ore".into();
+        character.task_type = "items".into();
+        character.task_total = 3;
+    });
+    let character = server.character().await;
+
+    // gathering the items alone doesn't count towards the task
+    character.move_to(&TASK, 2, 0).await.unwrap().await.unwrap();
+    for _ in 0..2 {
+        character.gathering(&TASK).await.unwrap().await.unwrap();
+    }
+    assert_eq!(character.read_state(|c| c.task_progress), 0);
+
+    character.move_to(&TASK, 3, 2).await.unwrap().await.unwrap();
+    let data = character
+        .task_trade(&TASK, "copper_ore", 2)
+        .await
+        .unwrap()
+        .await
+        .unwrap();
+    assert_eq!(data.trade.unwrap().quantity, 2);
+    assert_eq!(character.read_state(|c| c.task_progress), 2);
+    assert_eq!(character.inventory_count("copper_ore"), 0);
+
+    let too_many = character
+        .task_trade(&TASK, "copper_ore", 2)
+        .await
+        .unwrap()
+        .await;
+    assert!(matches!(too_many, Err(Error::APIError(e)) if e.code == 475));
+}
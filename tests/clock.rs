@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use artifacts::clock::Clock;
+use tokio::time::Instant;
+
+const SECOND: chrono::Duration = chrono::Duration::seconds(1);
+
+fn ms(n: i64) -> chrono::Duration {
+    chrono::Duration::milliseconds(n)
+}
+
+#[tokio::test(start_paused = true)]
+async fn skew_narrows_with_each_response() {
+    let clock = Clock::new();
+    // no skew yet, so this is the local time
+    let local = clock.now();
+    let start = Instant::now();
+    assert_eq!(clock.skew(), ms(0));
+
+    // the server was between 4.8s and 6s ahead while the request was in flight
+    clock.observe(
+        start,
+        start + Duration::from_millis(200),
+        local + ms(5000),
+        SECOND,
+    );
+    assert_eq!(clock.skew(), ms(4800));
+
+    // between 5.2s and 6.3s ahead, which narrows it down to 5.2s to 6s
+    tokio::time::advance(Duration::from_secs(10)).await;
+    let sent = Instant::now();
+    clock.observe(
+        sent,
+        sent + Duration::from_millis(100),
+        local + ms(15_300),
+        SECOND,
+    );
+    assert_eq!(clock.skew(), ms(5200));
+
+    assert_eq!(clock.now(), local + ms(15_200));
+    assert_eq!(clock.until(local + ms(20_000)), Duration::from_millis(4800));
+    assert_eq!(clock.until(local), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn skew_starts_over_when_the_clocks_drift_apart() {
+    let clock = Clock::new();
+    let local = clock.now();
+    let start = Instant::now();
+
+    clock.observe(start, start, local + ms(5000), SECOND);
+    assert_eq!(clock.skew(), ms(5000));
+
+    // 8s to 9s ahead can't be reconciled with 5s to 6s
+    clock.observe(start, start, local + ms(8000), SECOND);
+    assert_eq!(clock.skew(), ms(8000));
+
+    // and later measurements narrow down the new range
+    clock.observe(start, start, local + ms(8500), SECOND);
+    assert_eq!(clock.skew(), ms(8500));
+}
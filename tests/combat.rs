@@ -0,0 +1,101 @@
+mod common;
+
+use artifacts::{
+    combat::predict,
+    config::{HealPolicy, Task, TaskType},
+    mock::fixtures,
+    tasks,
+};
+use common::{Server, NAME};
+
+#[tokio::test]
+async fn predicts_wins_and_losses() {
+    let server = Server::start().await;
+    let character = server.world(|world| world.character(NAME).unwrap().clone());
+    let monsters = fixtures::monsters();
+    let monster = |code| monsters.iter().find(|m| m.code == code).unwrap();
+
+    let chicken = predict(&character, monster("chicken"));
+    assert_eq!(chicken.win_probability, 1.0);
+    assert!(chicken.hp_loss > 0.0 && chicken.hp_loss < character.hp as f64);
+
+    let slime = predict(&character, monster("yellow_slime"));
+    assert!(!slime.is_win());
+}
+
+#[tokio::test]
+async fn fight_task_refuses_losing_fights() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::Fight {
+        monster: "yellow_slime".into(),
+        times: 1,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    // the character didn't even walk to the slime
+    assert_eq!(character.read_state(|c| (c.x, c.y)), (0, 0));
+    assert_eq!(character.read_state(|c| c.xp), 0);
+}
+
+#[tokio::test]
+async fn fight_task_rests_before_refusing() {
+    let server = Server::start().await;
+    server.world(|world| world.character_mut(NAME).unwrap().hp = 1);
+    // a policy that never heals, so only the prediction makes the character rest
+    let character = server
+        .character_with(|character| {
+            character.with_heal_policy(HealPolicy {
+                below_hp_percent: 0.0,
+                eat_food: false,
+            })
+        })
+        .await;
+
+    let task = Task::Fight {
+        monster: "chicken".into(),
+        times: 1,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert!(character.read_state(|c| c.xp) > 0);
+}
+
+#[tokio::test]
+async fn task_master_cancels_unwinnable_tasks() {
+    let server = Server::start().await;
+    server.world(|world| {
+        let character = world.character_mut(NAME).unwrap();
+        character.task = "yellow_slime".into();
+        character.task_type = "monsters".into();
+        character.task_total = 5;
+        world.give(NAME, "tasks_coin", 1);
+    });
+    let character = server.character().await;
+
+    let task = Task::TaskMaster {
+        task_type: TaskType::Monsters,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert!(character.read_state(|c| c.task.is_empty()));
+    assert_eq!(character.inventory_count("tasks_coin"), 0);
+}
+
+#[tokio::test]
+async fn produce_refuses_losing_fights() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::Produce {
+        item: "yellow_slimeball".into(),
+        quantity: 1,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.read_state(|c| (c.x, c.y)), (0, 0));
+    assert_eq!(character.inventory_count("yellow_slimeball"), 0);
+}
@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+use std::{
+    convert::Infallible,
+    sync::{Arc, Mutex},
+};
+
+use artifacts::{
+    client::Client,
+    game_data::GameData,
+    mock::{Cooldowns, World},
+    Character,
+};
+use chrono::Utc;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::{Bytes, Incoming},
+    header, Request, Response,
+};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+
+pub const TOKEN: &str = "secret";
+pub const NAME: &str = "ducc";
+
+/// A mock server listening on localhost, serving requests from a shared world
+pub struct Server {
+    pub url: String,
+    pub world: Arc<Mutex<World>>,
+}
+
+impl Server {
+    /// A world with one character and short cooldowns so tests run quickly
+    pub async fn start() -> Self {
+        let mut world = World::new(0)
+            .with_token(TOKEN)
+            .with_cooldowns(Cooldowns::uniform(0.05));
+        world.add_character(NAME);
+
+        Self::with_world(world).await
+    }
+
+    pub async fn with_world(world: World) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let world = Arc::new(Mutex::new(world));
+
+        let shared = world.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let world = shared.clone();
+
+                tokio::spawn(async move {
+                    let service = hyper::service::service_fn(move |request| {
+                        let world = world.clone();
+                        async move { handle(world, request).await }
+                    });
+
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+        });
+
+        Self { url, world }
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new(reqwest::Client::new(), &self.url, format!("Bearer {TOKEN}"))
+    }
+
+    /// Load the character and run its execution loop in the background
+    pub async fn character(&self) -> Arc<Character> {
+        self.character_with(|character| character).await
+    }
+
+    /// Like `character`, configuring the character with `f` before it starts
+    pub async fn character_with(&self, f: impl FnOnce(Character) -> Character) -> Arc<Character> {
+        let client = self.client();
+        let game = Arc::new(GameData::new(client.clone()));
+        let character = Character::load(client, NAME.to_string(), game)
+            .await
+            .unwrap();
+        let character = Arc::new(f(character));
+
+        let executor = character.clone();
+        tokio::spawn(async move { executor.execution_loop().await });
+
+        character
+    }
+
+    pub fn world<T>(&self, f: impl FnOnce(&mut World) -> T) -> T {
+        f(&mut self.world.lock().unwrap())
+    }
+}
+
+async fn handle(
+    world: Arc<Mutex<World>>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().unwrap_or_default().to_string();
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let body = request.into_body().collect().await.unwrap().to_bytes();
+
+    let (status, body) =
+        world
+            .lock()
+            .unwrap()
+            .handle(&method, &path, &query, token.as_deref(), &body, Utc::now());
+
+    Ok(Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .unwrap())
+}
@@ -0,0 +1,132 @@
+use artifacts::{
+    conditions::evaluate, config::Condition, inventory::Inventory, response::Character,
+};
+
+fn character() -> Character {
+    let mut inventory = Inventory::new(3, 100);
+    inventory.insert("copper_ore", 10);
+    inventory.insert("cooked_chicken", 5);
+
+    Character {
+        hp: 40,
+        gold: 500,
+        level: 3,
+        mining_level: 5,
+        inventory,
+        ..Default::default()
+    }
+}
+
+fn condition(source: &str) -> Condition {
+    #[derive(serde::Deserialize)]
+    struct Wrapper {
+        condition: Condition,
+    }
+
+    toml::from_str::<Wrapper>(&format!("condition = {source}"))
+        .unwrap_or_else(|e| panic!("{source}: {e}"))
+        .condition
+}
+
+fn assert_table(character: &Character, table: &[(&str, bool)]) {
+    for (source, expected) in table {
+        assert_eq!(
+            evaluate(&condition(source), character),
+            *expected,
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn leaves() {
+    assert_table(
+        &character(),
+        &[
+            (r#""FullInventory""#, false),
+            ("{ HpBelow = 41 }", true),
+            ("{ HpBelow = 40 }", false),
+            (
+                r#"{ ItemCountAtLeast = { code = "copper_ore", n = 10 } }"#,
+                true,
+            ),
+            (
+                r#"{ ItemCountAtLeast = { code = "copper_ore", n = 11 } }"#,
+                false,
+            ),
+            (
+                r#"{ ItemCountAtLeast = { code = "iron_ore", n = 1 } }"#,
+                false,
+            ),
+            (
+                r#"{ SkillLevelAtLeast = { skill = "mining", level = 5 } }"#,
+                true,
+            ),
+            (
+                r#"{ SkillLevelAtLeast = { skill = "combat", level = 4 } }"#,
+                false,
+            ),
+            ("{ GoldAtLeast = 500 }", true),
+            ("{ GoldAtLeast = 501 }", false),
+            // one of the three slots is free
+            ("{ InventoryFreeSlotsBelow = 2 }", true),
+            ("{ InventoryFreeSlotsBelow = 1 }", false),
+            // no task at all isn't a complete one
+            (r#""TaskProgressComplete""#, false),
+        ],
+    );
+}
+
+#[test]
+fn full_inventory() {
+    let mut full = character();
+    full.inventory.insert("feather", 85);
+
+    assert_table(
+        &full,
+        &[
+            (r#""FullInventory""#, true),
+            ("{ InventoryFreeSlotsBelow = 1 }", true),
+        ],
+    );
+}
+
+#[test]
+fn task_progress() {
+    let mut character = character();
+    character.task = "chicken".into();
+    character.task_total = 5;
+
+    character.task_progress = 4;
+    assert_table(&character, &[(r#""TaskProgressComplete""#, false)]);
+
+    character.task_progress = 5;
+    assert_table(&character, &[(r#""TaskProgressComplete""#, true)]);
+}
+
+#[test]
+fn combinators() {
+    assert_table(
+        &character(),
+        &[
+            ("{ All = [] }", true),
+            ("{ Any = [] }", false),
+            (
+                r#"{ All = [{ HpBelow = 50 }, { GoldAtLeast = 100 }] }"#,
+                true,
+            ),
+            (r#"{ All = [{ HpBelow = 50 }, "FullInventory"] }"#, false),
+            (
+                r#"{ Any = ["FullInventory", { GoldAtLeast = 100 }] }"#,
+                true,
+            ),
+            (r#"{ Any = ["FullInventory", { HpBelow = 10 }] }"#, false),
+            (r#"{ Not = "FullInventory" }"#, true),
+            (r#"{ Not = { Not = "FullInventory" } }"#, false),
+            (
+                r#"{ Any = [{ All = ["FullInventory", { HpBelow = 50 }] }, { Not = { GoldAtLeast = 1000 } }] }"#,
+                true,
+            ),
+        ],
+    );
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use std::{ops::Deref, path::Path};
+
+use artifacts::{
+    config::{HistoryConfig, Task},
+    history::History,
+};
+use common::{Server, NAME};
+use serde_json::Value;
+
+const TASK: Task = Task::Move { x: 0, y: 0 };
+
+/// A history in the temp dir, removed along with its rotated files when dropped
+struct TempHistory {
+    history: History,
+    keep: u32,
+}
+
+impl Deref for TempHistory {
+    type Target = History;
+
+    fn deref(&self) -> &History {
+        &self.history
+    }
+}
+
+impl Drop for TempHistory {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(self.path());
+        for n in 1..=self.keep {
+            let _ = std::fs::remove_file(self.rotated(n));
+        }
+    }
+}
+
+fn history(max_bytes: u64, keep: u32) -> TempHistory {
+    let path = std::env::temp_dir().join(format!("history-{}.jsonl", uuid::Uuid::new_v4()));
+
+    let history = History::open(&HistoryConfig {
+        path,
+        max_bytes,
+        keep,
+    })
+    .unwrap();
+
+    TempHistory { history, keep }
+}
+
+fn lines(path: impl AsRef<Path>) -> Vec<Value> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn records_actions_with_changes() {
+    let server = Server::start().await;
+    let history = history(u64::MAX, 1);
+    let character = server
+        .character_with(|character| character.with_history(history.clone()))
+        .await;
+
+    character.move_to(&TASK, 2, 0).await.unwrap().await.unwrap();
+    character.gathering(&TASK).await.unwrap().await.unwrap();
+    let _ = character.move_to(&TASK, 2, 0).await.unwrap().await;
+    history.flush().await.unwrap();
+
+    let entries = lines(history.path());
+    assert_eq!(entries.len(), 3);
+
+    let moved = &entries[0];
+    assert_eq!(moved["character"], NAME);
+    assert_eq!(moved["task"], "Move { x: 0, y: 0 }");
+    assert_eq!(moved["action"], "move");
+    assert_eq!(moved["request"], serde_json::json!({ "x": 2, "y": 0 }));
+    assert_eq!(moved["status"], 200);
+
+    let gathered = &entries[1];
+    assert!(gathered["cooldown_seconds"].is_i64());
+    assert_eq!(gathered["xp"]["mining"], 10);
+    assert_eq!(gathered["items"]["copper_ore"], 1);
+
+    let already_there = &entries[2];
+    assert_eq!(already_there["status"], 490);
+    assert!(already_there["error"].is_string());
+}
+
+#[tokio::test]
+async fn rotates_full_files() {
+    let server = Server::start().await;
+    let history = history(1, 1);
+    let character = server
+        .character_with(|character| character.with_history(history.clone()))
+        .await;
+
+    for x in 1..=3 {
+        character.move_to(&TASK, x, 0).await.unwrap().await.unwrap();
+    }
+    history.flush().await.unwrap();
+
+    // every line fills the file, so only the last two are kept
+    let current = lines(history.path());
+    assert_eq!(current[0]["request"]["x"], 3);
+    assert_eq!(lines(history.rotated(1))[0]["request"]["x"], 2);
+    assert!(!history.rotated(2).exists());
+}
@@ -0,0 +1,65 @@
+use artifacts::{inventory::Inventory, response::Character};
+use serde_json::{json, Value};
+
+// A character from the API with `inventory` fields in place of the default ones
+fn character(inventory: Value) -> Character {
+    let mut character = serde_json::to_value(Character::default()).unwrap();
+    let fields = character.as_object_mut().unwrap();
+    fields.remove("inventory");
+    fields.extend(inventory.as_object().unwrap().clone());
+
+    serde_json::from_value(character).unwrap()
+}
+
+#[test]
+fn deserializes_inventory_array() {
+    let character = character(json!({
+        "inventory_max_items": 100,
+        "inventory": [
+            { "slot": 1, "code": "copper_ore", "quantity": 3 },
+            { "slot": 2, "code": "", "quantity": 0 },
+            { "slot": 3, "code": "copper_ore", "quantity": 2 },
+        ],
+    }));
+
+    let inventory = &character.inventory;
+    assert_eq!(inventory.slots().len(), 3);
+    assert_eq!(inventory.count("copper_ore"), 5);
+    assert_eq!(inventory.free_slots(), 1);
+    assert_eq!(inventory.max_items(), 100);
+}
+
+#[test]
+fn deserializes_legacy_slot_fields() {
+    let character = character(json!({
+        "inventory_max_items": 4,
+        "inventory_slot1": "ash_wood",
+        "inventory_slot1_quantity": 4,
+        "inventory_slot2": "",
+        "inventory_slot2_quantity": 0,
+    }));
+
+    let inventory = &character.inventory;
+    assert_eq!(inventory.slots().len(), 2);
+    assert_eq!(inventory.total_items(), 4);
+    assert!(inventory.is_full());
+
+    // round trips through the array format
+    let character: Character =
+        serde_json::from_value(serde_json::to_value(&character).unwrap()).unwrap();
+    assert_eq!(character.inventory.count("ash_wood"), 4);
+}
+
+#[test]
+fn adds_and_removes_items() {
+    let mut inventory = Inventory::new(2, 10);
+
+    assert!(inventory.add("copper_ore", 6));
+    assert!(!inventory.add("iron_ore", 5));
+    assert!(inventory.add("iron_ore", 4));
+    assert!(inventory.is_full());
+
+    assert!(!inventory.remove("copper_ore", 7));
+    assert!(inventory.remove("copper_ore", 6));
+    assert_eq!(inventory.free_slots(), 1);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use artifacts::{
+    config::Task,
+    metrics::{self, Metrics},
+};
+use common::{Server, NAME};
+use tokio::net::TcpListener;
+
+const TASK: Task = Task::Move { x: 0, y: 0 };
+
+#[tokio::test]
+async fn counts_actions_and_serves_them() {
+    let server = Server::start().await;
+    let metrics = Metrics::new();
+    let character = server
+        .character_with(|character| character.with_metrics(metrics.clone()))
+        .await;
+
+    character.move_to(&TASK, 2, 0).await.unwrap().await.unwrap();
+    character.gathering(&TASK).await.unwrap().await.unwrap();
+    character.gathering(&TASK).await.unwrap().await.unwrap();
+    let _ = character.fight(&TASK).await.unwrap().await;
+
+    let rendered = metrics.render();
+    for line in [
+        format!("artifacts_actions_total{{character=\"{NAME}\",action=\"move\"}} 1"),
+        format!("artifacts_actions_total{{character=\"{NAME}\",action=\"gathering\"}} 2"),
+        format!(
+            "artifacts_api_errors_total{{character=\"{NAME}\",action=\"fight\",code=\"598\"}} 1"
+        ),
+        format!("artifacts_items_gathered_total{{character=\"{NAME}\",item=\"copper_ore\"}} 2"),
+        format!("artifacts_xp_gained_total{{character=\"{NAME}\",skill=\"mining\"}} 20"),
+        format!("artifacts_deaths_total{{character=\"{NAME}\"}} 0"),
+        format!("artifacts_queue_depth{{character=\"{NAME}\"}} 0"),
+    ] {
+        assert!(rendered.contains(&line), "{line} missing from\n{rendered}");
+    }
+    // the rejected fight wasn't executed
+    assert!(!rendered.contains("action=\"fight\"} "));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/metrics", listener.local_addr().unwrap());
+    tokio::spawn(metrics::serve(metrics, listener));
+
+    let body = reqwest::get(url).await.unwrap().text().await.unwrap();
+    assert!(body.contains("# TYPE artifacts_actions_total counter"));
+}
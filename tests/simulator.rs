@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use artifacts::{config::Config, simulator::simulate};
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn config(tasks: &str) -> Config {
+    toml::from_str(&format!("[[characters]]\nname = \"ducc\"\n{tasks}")).unwrap()
+}
+
+#[test]
+fn gathering_with_deposits_keeps_going() {
+    let config = config(
+        r#"
+        [[characters.tasks]]
+        kind = "Gather"
+        resource = "copper_rocks"
+        times = 10
+
+        [[characters.tasks]]
+        kind = "DepositInventory"
+        condition = "FullInventory"
+        "#,
+    );
+
+    let report = simulate(&config, 2 * HOUR, 0).unwrap();
+    let character = &report.characters[0];
+
+    // copper rocks always drop one ore and take 25s
+    assert!(report.per_hour(report.items()["copper_ore"]) > 100.0);
+    assert!(report.bank["copper_ore"] >= 100);
+    assert!(character.xp > 0);
+    assert!(character.idle < Duration::from_secs(60));
+}
+
+#[test]
+fn full_inventory_stalls() {
+    let config = config(
+        r#"
+        [[characters.tasks]]
+        kind = "Gather"
+        resource = "copper_rocks"
+        times = 10
+        "#,
+    );
+
+    let report = simulate(&config, 2 * HOUR, 0).unwrap();
+
+    assert_eq!(report.items()["copper_ore"], 100);
+    assert!(report.characters[0].idle > 10 * 60 * Duration::from_secs(1));
+}
+
+#[test]
+fn same_seed_same_report() {
+    let config = config(
+        r#"
+        [[characters.tasks]]
+        kind = "Fight"
+        monster = "chicken"
+        times = 5
+        "#,
+    );
+
+    let first = simulate(&config, HOUR, 7).unwrap();
+    let second = simulate(&config, HOUR, 7).unwrap();
+
+    assert_eq!(first.items(), second.items());
+    assert_eq!(first.gold(), second.gold());
+    assert!(first.characters[0].xp > 0);
+}
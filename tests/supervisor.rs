@@ -0,0 +1,210 @@
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use artifacts::{
+    client::Client,
+    clock::Clock,
+    config::{RetryPolicy, Task, TaskConfig},
+    game_data::GameData,
+    mock::{Cooldowns, World},
+    supervisor::{CharacterHealth, IncidentKind, Status, Supervisor},
+    Character,
+};
+use common::{Server, NAME};
+
+fn move_to(x: i32, y: i32) -> Vec<TaskConfig> {
+    vec![TaskConfig {
+        task: Task::Move { x, y },
+        condition: None,
+    }]
+}
+
+async fn wait_for(character: &Character, position: (i64, i64)) {
+    for _ in 0..100 {
+        if character.read_state(|c| (c.x, c.y)) == position {
+            return;
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    panic!("character never reached {position:?}");
+}
+
+fn gather_iron() -> Vec<TaskConfig> {
+    // iron needs a higher mining level than a new character has
+    vec![TaskConfig {
+        task: Task::Gather {
+            resource: "iron_rocks".into(),
+            times: 1,
+            position: None,
+        },
+        condition: None,
+    }]
+}
+
+/// Poll the character's health until `done` holds for it
+async fn wait_for_health(
+    supervisor: &Supervisor,
+    mut done: impl FnMut(&CharacterHealth) -> bool,
+) -> CharacterHealth {
+    for _ in 0..2000 {
+        if let Some(health) = supervisor.health().remove(NAME) {
+            if done(&health) {
+                return health;
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    panic!(
+        "character health never changed as expected: {:?}",
+        supervisor.health()
+    );
+}
+
+#[tokio::test]
+async fn swaps_tasks_and_stops() {
+    let server = Server::start().await;
+    let client = server.client();
+    let game = Arc::new(GameData::new(client.clone()));
+    let character = Arc::new(
+        Character::load(client, NAME.to_string(), game)
+            .await
+            .unwrap(),
+    );
+
+    let supervisor = Supervisor::new(RetryPolicy::default());
+    supervisor.supervise(character.clone(), move_to(2, 0));
+    wait_for(&character, (2, 0)).await;
+
+    assert!(supervisor.set_tasks(NAME, move_to(0, 1)));
+    wait_for(&character, (0, 1)).await;
+
+    assert!(supervisor.stop(NAME));
+    assert!(supervisor.characters().is_empty());
+    assert!(!supervisor.set_tasks(NAME, move_to(2, 0)));
+}
+
+#[test]
+fn backoff_grows_up_to_the_max() {
+    let policy = RetryPolicy {
+        initial_backoff_secs: 5.0,
+        max_backoff_secs: 30.0,
+        multiplier: 2.0,
+    };
+
+    let backoffs: Vec<f64> = (1..=5).map(|n| policy.backoff(n).as_secs_f64()).collect();
+    assert_eq!(backoffs, [5.0, 10.0, 20.0, 30.0, 30.0]);
+}
+
+#[tokio::test]
+async fn restarts_failing_tasks_with_backoff() {
+    let server = Server::start().await;
+    let client = server.client();
+    let game = Arc::new(GameData::new(client.clone()));
+    let character = Arc::new(
+        Character::load(client, NAME.to_string(), game)
+            .await
+            .unwrap(),
+    );
+
+    let policy = RetryPolicy {
+        initial_backoff_secs: 0.2,
+        max_backoff_secs: 0.4,
+        multiplier: 2.0,
+    };
+    let supervisor = Supervisor::new(policy.clone());
+    supervisor.supervise(character, gather_iron());
+
+    let mut incidents = vec![];
+    for failures in 1..=3 {
+        let health = wait_for_health(&supervisor, |health| {
+            health.consecutive_failures == failures
+        })
+        .await;
+
+        // unhealthy while it waits out the backoff
+        assert_eq!(health.status, Status::Restarting);
+        assert!(!supervisor.is_healthy());
+        assert_eq!(health.restarts, failures as u64);
+
+        let incident = health.last_incident.unwrap();
+        assert_eq!(incident.kind, IncidentKind::Error);
+        assert!(incident.task.unwrap().contains("iron_rocks"));
+        incidents.push(incident.at);
+    }
+
+    // each restart waits at least the backoff for the failures so far
+    for (failures, pair) in (1..).zip(incidents.windows(2)) {
+        let waited = (pair[1] - pair[0]).to_std().unwrap();
+        assert!(
+            waited >= policy.backoff(failures),
+            "{waited:?} after {failures}"
+        );
+    }
+
+    // and the character is running again once the backoff is over
+    wait_for_health(&supervisor, |health| health.status == Status::Running).await;
+}
+
+#[tokio::test]
+async fn panics_are_incidents() {
+    let world = {
+        let mut world = World::new(0).with_cooldowns(Cooldowns::uniform(0.05));
+        world.add_character(NAME);
+        Arc::new(Mutex::new(world))
+    };
+    let client = Client::local(world.clone(), Clock::new());
+    let game = Arc::new(GameData::new(client.clone()));
+    let character = Arc::new(
+        Character::load(client, NAME.to_string(), game)
+            .await
+            .unwrap(),
+    );
+
+    // a poisoned world makes every request panic, and producing starts with fetching items
+    std::thread::spawn(move || {
+        let _world = world.lock().unwrap();
+        panic!("poisoning the world");
+    })
+    .join()
+    .unwrap_err();
+
+    let supervisor = Supervisor::new(RetryPolicy {
+        initial_backoff_secs: 0.2,
+        max_backoff_secs: 0.2,
+        multiplier: 1.0,
+    });
+    supervisor.supervise(
+        character,
+        vec![TaskConfig {
+            task: Task::Produce {
+                item: "copper".into(),
+                quantity: 1,
+            },
+            condition: None,
+        }],
+    );
+
+    let health = wait_for_health(&supervisor, |health| {
+        health
+            .last_incident
+            .as_ref()
+            .is_some_and(|incident| incident.kind == IncidentKind::Panic)
+    })
+    .await;
+
+    let incident = health.last_incident.unwrap();
+    assert_eq!(incident.task, None);
+    assert!(
+        incident.message.contains("world lock poisoned"),
+        "{incident:?}"
+    );
+    assert_eq!(health.status, Status::Restarting);
+}
@@ -0,0 +1,367 @@
+mod common;
+
+use artifacts::{
+    config::{Activity, DepositPolicy, Position, Task, TaskType},
+    error::Error,
+    inventory::Inventory,
+    tasks,
+};
+use common::{Server, NAME};
+
+#[tokio::test]
+async fn gather_resolves_location() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::Gather {
+        resource: "copper_rocks".into(),
+        times: 3,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.read_state(|c| (c.x, c.y)), (2, 0));
+    assert_eq!(character.inventory_count("copper_ore"), 3);
+}
+
+#[tokio::test]
+async fn failed_move_stops_the_task() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::Gather {
+        resource: "copper_rocks".into(),
+        times: 1,
+        position: Some(Position { x: 40, y: 0 }),
+    };
+    let result = tasks::run(&character, &task).await;
+
+    assert!(matches!(result, Err(Error::APIError(e)) if e.code == 404));
+    assert_eq!(character.inventory_count("copper_ore"), 0);
+}
+
+#[tokio::test]
+async fn move_off_the_map_fails() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let result = tasks::run(&character, &Task::Move { x: 40, y: 0 }).await;
+
+    assert!(matches!(result, Err(Error::APIError(e)) if e.code == 404));
+}
+
+#[tokio::test]
+async fn craft_fails_on_unexpected_errors() {
+    let server = Server::start().await;
+    server.world(|world| world.give(NAME, "iron_ore", 10));
+    let character = server.character().await;
+
+    let task = Task::Craft {
+        item: "iron".into(),
+        quantity: 1,
+        position: None,
+    };
+    let result = tasks::run(&character, &task).await;
+
+    assert!(matches!(result, Err(Error::SkillLevelTooLow)));
+    assert_eq!(character.inventory_count("iron_ore"), 10);
+}
+
+#[tokio::test]
+async fn gather_fails_on_unexpected_errors() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::Gather {
+        resource: "iron_rocks".into(),
+        times: 3,
+        position: None,
+    };
+    let result = tasks::run(&character, &task).await;
+
+    assert!(matches!(result, Err(Error::SkillLevelTooLow)));
+}
+
+#[tokio::test]
+async fn gather_stops_on_full_inventory() {
+    let server = Server::start().await;
+    server.world(|world| world.give(NAME, "copper_ore", 100));
+    let character = server.character().await;
+
+    let task = Task::Gather {
+        resource: "copper_rocks".into(),
+        times: 3,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+    let executed = character.executed_actions();
+    tasks::run(&character, &task).await.unwrap();
+
+    // nothing succeeded, so the supervisor waits before the next pass
+    assert_eq!(character.executed_actions(), executed);
+}
+
+#[tokio::test]
+async fn produce_follows_plan() {
+    let server = Server::start().await;
+    server.world(|world| world.deposit_in_bank("copper_ore", 5));
+    let character = server.character().await;
+
+    let task = Task::Produce {
+        item: "copper".into(),
+        quantity: 2,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.inventory_count("copper"), 2);
+    assert!(server.world(|world| world.bank().is_empty()));
+}
+
+#[tokio::test]
+async fn produce_more_than_fits_in_the_inventory() {
+    let server = Server::start().await;
+    // a copper takes 10 ore, so only one copper's worth fits at a time
+    server.world(|world| world.character_mut(NAME).unwrap().inventory = Inventory::new(20, 15));
+    let character = server.character().await;
+
+    let task = Task::Produce {
+        item: "copper".into(),
+        quantity: 2,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.inventory_count("copper"), 2);
+    assert_eq!(character.inventory_count("copper_ore"), 0);
+}
+
+#[tokio::test]
+async fn produce_fails_on_unexpected_errors() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::Produce {
+        item: "iron".into(),
+        quantity: 1,
+    };
+    let result = tasks::run(&character, &task).await;
+
+    assert!(matches!(result, Err(Error::SkillLevelTooLow)));
+}
+
+#[tokio::test]
+async fn fight_heals_first() {
+    let server = Server::start().await;
+    server.world(|world| {
+        world.character_mut(NAME).unwrap().hp = 20;
+        world.give(NAME, "cooked_chicken", 3);
+    });
+    let character = server.character().await;
+
+    let task = Task::Fight {
+        monster: "chicken".into(),
+        times: 1,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    // two chickens heal the missing 100 hp
+    assert_eq!(character.inventory_count("cooked_chicken"), 1);
+    assert!(character.read_state(|c| c.xp) > 0);
+}
+
+#[tokio::test]
+async fn rest_without_food() {
+    let server = Server::start().await;
+    server.world(|world| world.character_mut(NAME).unwrap().hp = 20);
+    let character = server.character().await;
+
+    tasks::heal(&character, &Task::KillChickens).await.unwrap();
+
+    assert_eq!(character.read_state(|c| c.hp), 120);
+}
+
+#[tokio::test]
+async fn craft_withdraws_materials() {
+    let server = Server::start().await;
+    server.world(|world| {
+        world.give(NAME, "copper_ore", 4);
+        world.deposit_in_bank("copper_ore", 30);
+    });
+    let character = server.character().await;
+
+    let task = Task::Craft {
+        item: "copper".into(),
+        quantity: 2,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.inventory_count("copper"), 2);
+    assert_eq!(character.inventory_count("copper_ore"), 0);
+    let bank = character.bank().items().await.unwrap();
+    assert_eq!(bank["copper_ore"], 14);
+}
+
+#[tokio::test]
+async fn deposit_inventory_follows_policy() {
+    let server = Server::start().await;
+    server.world(|world| {
+        world.give(NAME, "copper_ore", 30);
+        world.give(NAME, "cooked_chicken", 12);
+        world.give(NAME, "feather", 3);
+        world.character_mut(NAME).unwrap().gold = 250;
+    });
+    let policy: DepositPolicy = toml::from_str(
+        r#"
+        keep = { cooked_chicken = 10 }
+        only = ["copper_ore", "cooked_chicken"]
+        gold_above = 100
+        "#,
+    )
+    .unwrap();
+    let character = server
+        .character_with(|character| character.with_deposit_policy(policy))
+        .await;
+
+    tasks::run(&character, &Task::DepositInventory)
+        .await
+        .unwrap();
+
+    assert_eq!(character.inventory_count("copper_ore"), 0);
+    assert_eq!(character.inventory_count("cooked_chicken"), 10);
+    assert_eq!(character.inventory_count("feather"), 3);
+    assert_eq!(character.read_state(|c| c.gold), 100);
+    assert_eq!(server.world(|world| world.bank_gold()), 150);
+}
+
+#[tokio::test]
+async fn sell_surplus() {
+    let server = Server::start().await;
+    server.world(|world| world.give(NAME, "copper_ore", 80));
+    let character = server.character().await;
+
+    let too_cheap = Task::Sell {
+        code: "copper_ore".into(),
+        min_price: 5,
+        keep: 0,
+        position: None,
+    };
+    tasks::run(&character, &too_cheap).await.unwrap();
+    assert_eq!(character.inventory_count("copper_ore"), 80);
+
+    let task = Task::Sell {
+        code: "copper_ore".into(),
+        min_price: 4,
+        keep: 10,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    // in two transactions of at most 50
+    assert_eq!(character.inventory_count("copper_ore"), 10);
+    assert_eq!(character.read_state(|c| c.gold), 70 * 4);
+}
+
+#[tokio::test]
+async fn buy_what_is_affordable() {
+    let server = Server::start().await;
+    server.world(|world| {
+        world.give(NAME, "cooked_chicken", 2);
+        world.character_mut(NAME).unwrap().gold = 100;
+    });
+    let character = server.character().await;
+
+    let task = Task::Buy {
+        code: "cooked_chicken".into(),
+        max_price: 25,
+        quantity: 10,
+        position: None,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    // 100 gold buys 4 at 22 each
+    assert_eq!(character.inventory_count("cooked_chicken"), 6);
+    assert_eq!(character.read_state(|c| c.gold), 12);
+    let listing = character
+        .grand_exchange()
+        .listing("cooked_chicken")
+        .await
+        .unwrap();
+    assert_eq!(listing.stock, 396);
+}
+
+#[tokio::test]
+async fn task_master_turns_in_monsters_task() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::TaskMaster {
+        task_type: TaskType::Monsters,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.inventory_count("tasks_coin"), 1);
+    assert!(character.read_state(|c| c.task.is_empty()));
+    assert_eq!(character.read_state(|c| (c.x, c.y)), (1, 2));
+}
+
+#[tokio::test]
+async fn task_master_produces_items() {
+    let server = Server::start().await;
+    let character = server.character().await;
+
+    let task = Task::TaskMaster {
+        task_type: TaskType::Items,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(character.inventory_count("tasks_coin"), 1);
+    assert!(character.read_state(|c| c.task.is_empty()));
+    // the items were traded at the items tasks master
+    assert_eq!(character.read_state(|c| (c.x, c.y)), (3, 2));
+}
+
+#[tokio::test]
+async fn equip_best_for_fight() {
+    let server = Server::start().await;
+    server.world(|world| {
+        world.give(NAME, "copper_helmet", 1);
+        world.deposit_in_bank("copper_dagger", 1);
+        world.deposit_in_bank("iron_pickaxe", 1);
+    });
+    let character = server.character().await;
+
+    let task = Task::EquipBest {
+        activity: Activity::Fight("chicken".into()),
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    let state = character.state();
+    assert_eq!(state.weapon_slot, "copper_dagger");
+    assert_eq!(state.helmet_slot, "copper_helmet");
+    assert_eq!(character.inventory_count("wooden_stick"), 1);
+    // too high level to equip
+    assert_eq!(character.bank().count("iron_pickaxe").await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn equip_best_for_mining() {
+    let server = Server::start().await;
+    server.world(|world| {
+        world.character_mut(NAME).unwrap().level = 10;
+        world.give(NAME, "copper_dagger", 1);
+        world.give(NAME, "iron_pickaxe", 1);
+    });
+    let character = server.character().await;
+
+    let task = Task::EquipBest {
+        activity: Activity::Mining,
+    };
+    tasks::run(&character, &task).await.unwrap();
+
+    assert_eq!(
+        character.read_state(|c| c.weapon_slot.clone()),
+        "iron_pickaxe"
+    );
+    assert_eq!(character.inventory_count("copper_dagger"), 1);
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use artifacts::{
+    game_data::GameData,
+    validate::{validate, Diagnostic, Severity},
+};
+use common::Server;
+
+async fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let server = Server::start().await;
+    let game = GameData::new(server.client());
+
+    validate(source, &game).await.unwrap()
+}
+
+#[tokio::test]
+async fn accepts_valid_config() {
+    let source = r#"
+[[characters]]
+name = "ducc"
+
+[[characters.tasks]]
+kind = "Gather"
+resource = "copper_rocks"
+times = 10
+position = { x = 2, y = 0 }
+
+[[characters.tasks]]
+kind = "DepositInventory"
+condition = "FullInventory"
+"#;
+
+    assert!(diagnostics(source).await.is_empty());
+}
+
+#[tokio::test]
+async fn reports_syntax_errors_with_their_line() {
+    let source = "[[characters]]\nname = \"ducc\"\ntasks = [\n";
+
+    let diagnostics = diagnostics(source).await;
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].line, 4);
+}
+
+#[tokio::test]
+async fn reports_unknown_codes_and_positions() {
+    let source = r#"
+[[characters]]
+name = "ducc"
+
+[[characters.tasks]]
+kind = "Gather"
+resource = "iron_rock"
+times = 10
+
+[[characters.tasks]]
+kind = "Fight"
+monster = "chicken"
+times = 1
+position = { x = 40, y = 0 }
+
+[[characters.tasks]]
+kind = "Craft"
+item = "copper_ore"
+quantity = 1
+condition = { ItemCountAtLeast = { code = "copper_bar", n = 10 } }
+
+[[characters.tasks]]
+kind = "DepositInventory"
+condition = { HpBelow = 0 }
+"#;
+
+    let diagnostics: Vec<_> = diagnostics(source)
+        .await
+        .into_iter()
+        .map(|d| (d.line, d.severity, d.message))
+        .collect();
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            (7, Severity::Error, "unknown resource iron_rock".to_string()),
+            (14, Severity::Error, "(40, 0) is off the map".to_string()),
+            (
+                18,
+                Severity::Error,
+                "copper_ore can't be crafted".to_string()
+            ),
+            (20, Severity::Error, "unknown item copper_bar".to_string()),
+            (
+                24,
+                Severity::Warning,
+                "condition never holds, so the task never runs".to_string()
+            ),
+        ]
+    );
+}